@@ -0,0 +1,43 @@
+//! Compares sorting tree-shaped [`Packet`]s with sorting [`FlatPacket`]s.
+//!
+//! Run with `cargo bench --bench flat`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use advent_of_code_2022_13::{parse_packets, FlatPacket, Packet};
+
+const COPIES: usize = 200;
+const RUNS: usize = 10;
+
+/// Runs `f` on a fresh clone of `items` several times, returning the fastest run
+fn time<P: Clone>(items: &[P], mut f: impl FnMut(&mut Vec<P>)) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let mut items = items.to_vec();
+            let start = Instant::now();
+            f(black_box(&mut items));
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let input = format!("{}\n", include_str!("../input.txt")).repeat(COPIES);
+    let packets: Vec<Packet> = parse_packets(&input).collect::<Result<_, _>>().unwrap();
+    let flat: Vec<FlatPacket> = packets.iter().map(FlatPacket::from).collect();
+
+    println!("sorting {} packets, best of {RUNS}", packets.len());
+
+    let tree = time(&packets, |packets| packets.sort());
+    println!("  Packet:     {tree:?}");
+
+    let flat = time(&flat, |packets| packets.sort());
+    println!("  FlatPacket: {flat:?}");
+
+    println!(
+        "  speedup:    {:.2}x",
+        tree.as_secs_f64() / flat.as_secs_f64()
+    );
+}
@@ -0,0 +1,37 @@
+#![no_main]
+
+use std::cmp::Ordering;
+
+use advent_of_code_2022_13::{FlatPacket, Packet};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Up to three packets, one per line; lines that are not packets are skipped
+    let packets: Vec<Packet<u8>> = data
+        .split(|&b| b == b'\n')
+        .filter_map(|line| Packet::from_bytes(line).ok())
+        .take(3)
+        .collect();
+
+    for a in &packets {
+        assert_eq!(a.cmp(a), Ordering::Equal);
+
+        for b in &packets {
+            let ordering = a.cmp(b);
+            assert_eq!(b.cmp(a), ordering.reverse());
+            assert_eq!(a == b, ordering.is_eq());
+            assert_eq!(a.partial_cmp(b), Some(ordering));
+
+            // Every other way of ordering packets agrees
+            assert_eq!(FlatPacket::from(a).cmp(&FlatPacket::from(b)), ordering);
+            assert_eq!(a.to_sort_key().cmp(&b.to_sort_key()), ordering);
+            assert_eq!(a.explain_cmp(b).ordering, ordering);
+
+            for c in &packets {
+                if a <= b && b <= c {
+                    assert!(a <= c);
+                }
+            }
+        }
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use advent_of_code_2022_13::Pair;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        let _ = Pair::<u8>::from_bytes(data);
+        return;
+    };
+
+    // `Pair::new` panics only when `Pair::try_from_str` reports an error
+    let Ok(pair) = Pair::try_from_str(s) else {
+        return;
+    };
+    let new = Pair::new(s);
+    assert!(new.left().identical(pair.left()) && new.right().identical(pair.right()));
+    assert_eq!(pair.is_in_order(), pair.left() <= pair.right());
+
+    let printed = format!("{}\n{}", pair.left(), pair.right());
+    let reparsed = Pair::new(&printed);
+    assert!(reparsed.left().identical(pair.left()) && reparsed.right().identical(pair.right()));
+});
@@ -0,0 +1,29 @@
+#![no_main]
+
+use advent_of_code_2022_13::{BigInt, Packet};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Parsing must never panic, whatever the bytes
+    let bytes = Packet::<u8>::from_bytes(data);
+
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    let packet = s.parse::<Packet<u8>>();
+    assert_eq!(packet.is_ok(), bytes.is_ok());
+    let Ok(packet) = packet else {
+        return;
+    };
+
+    // Printing and parsing again gives back the same structure
+    let printed = packet.to_string();
+    let reparsed: Packet<u8> = printed.parse().expect("printed packet should parse");
+    assert!(reparsed.identical(&packet));
+    assert_eq!(reparsed.to_string(), printed);
+    assert_eq!(packet.cmp(&packet), std::cmp::Ordering::Equal);
+
+    // Any integer that fits in a u8 fits in a wider type too
+    let wide: Packet<BigInt> = s.parse().expect("u8 packet should parse as BigInt");
+    assert_eq!(wide.to_string(), printed);
+});
@@ -0,0 +1,24 @@
+#!/bin/sh
+# Derives a seed corpus for each fuzz target from the puzzle input
+set -eu
+
+cd "$(dirname "$0")"
+input=../input.txt
+
+mkdir -p corpus/parse corpus/pair corpus/compare
+
+# One packet per file
+awk 'NF { file = sprintf("corpus/parse/line-%03d", NR); print > file; close(file) }' "$input"
+
+# One pair per file, without the blank line after it
+awk 'BEGIN { RS = ""; ORS = "" } { file = sprintf("corpus/pair/pair-%03d", NR); print > file; close(file) }' "$input"
+
+# Three consecutive packets per file
+awk 'NF { lines[n++] = $0 }
+END {
+    for (i = 0; i + 2 < n; i++) {
+        file = sprintf("corpus/compare/triple-%03d", i)
+        print lines[i] "\n" lines[i + 1] "\n" lines[i + 2] > file
+        close(file)
+    }
+}' "$input"
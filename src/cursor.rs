@@ -0,0 +1,250 @@
+use std::mem;
+
+use crate::Packet;
+
+impl<T> Packet<T> {
+    /// The value at a path of item indices, outermost first, or `None` if the path
+    /// leads past the end of a list or into an integer
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::{packet, Packet};
+    ///
+    /// let mut packet: Packet = packet![1, [2, [3]]];
+    /// assert_eq!(packet.get_path(&[1, 1, 0]).unwrap().to_string(), "3");
+    /// assert_eq!(packet.get_path(&[]).unwrap().to_string(), "[1,[2,[3]]]");
+    /// assert!(packet.get_path(&[0, 0]).is_none());
+    /// assert!(packet.get_path(&[2]).is_none());
+    ///
+    /// *packet.get_path_mut(&[1, 0]).unwrap() = packet![4, 5];
+    /// assert_eq!(packet.to_string(), "[1,[[4,5],[3]]]");
+    /// ```
+    pub fn get_path(&self, path: &[usize]) -> Option<&Packet<T>> {
+        path.iter().try_fold(self, |value, &i| match value {
+            Packet::List(items) => items.get(i),
+            Packet::Int(_) => None,
+        })
+    }
+
+    /// Like [`get_path`](Self::get_path), but for changing the value
+    pub fn get_path_mut(&mut self, path: &[usize]) -> Option<&mut Packet<T>> {
+        path.iter().try_fold(self, |value, &i| match value {
+            Packet::List(items) => items.get_mut(i),
+            Packet::Int(_) => None,
+        })
+    }
+}
+
+/// A cursor for moving around a packet and editing it in place
+///
+/// The cursor owns the packet and is focused on one value within it, starting with the
+/// whole packet. Moving to a sibling and editing the focus take constant time; moving
+/// into or out of a list takes time proportional to its length. Take the edited packet
+/// back with [`into_packet`](Self::into_packet).
+///
+/// Moves return whether they succeeded, leaving the cursor where it was if not. Edits
+/// that cannot be made hand back the packet they were given.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{packet, Packet, PacketCursor};
+///
+/// let mut cursor: PacketCursor = PacketCursor::new(packet![1, [2, 3], 4]);
+/// assert!(cursor.descend(1));
+/// assert_eq!(cursor.path(), [1]);
+///
+/// cursor.wrap();
+/// assert!(cursor.next_sibling());
+/// assert_eq!(cursor.replace(Packet::Int(5)), Packet::Int(4));
+/// cursor.insert_after(packet![]).unwrap();
+/// assert!(cursor.prev_sibling() && cursor.prev_sibling());
+/// assert_eq!(cursor.remove(), Some(Packet::Int(1)));
+/// assert_eq!(cursor.path(), [0]);
+///
+/// assert!(cursor.insert_before(Packet::Int(0)).is_ok());
+/// assert!(cursor.ascend());
+/// assert!(cursor.insert_after(Packet::Int(9)).is_err());
+/// assert_eq!(cursor.into_packet().to_string(), "[0,[[2,3]],5,[]]");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PacketCursor<T = u8> {
+    focus: Packet<T>,
+    /// Each list enclosing the focus, outermost first
+    parents: Vec<Parent<T>>,
+}
+
+/// A list enclosing the focus of a [`PacketCursor`], taken apart around it
+#[derive(Debug, Clone)]
+struct Parent<T> {
+    /// The items before the focus
+    before: Vec<Packet<T>>,
+    /// The items after the focus in reverse, so that the nearest is last
+    after: Vec<Packet<T>>,
+}
+
+impl<T> PacketCursor<T> {
+    /// A cursor focused on the whole of a packet
+    pub fn new(packet: Packet<T>) -> Self {
+        PacketCursor {
+            focus: packet,
+            parents: Vec::new(),
+        }
+    }
+
+    /// The value in focus
+    pub fn current(&self) -> &Packet<T> {
+        &self.focus
+    }
+
+    /// The value in focus, for changing in place
+    pub fn current_mut(&mut self) -> &mut Packet<T> {
+        &mut self.focus
+    }
+
+    /// The index of the focus within each enclosing list, outermost first
+    pub fn path(&self) -> Vec<usize> {
+        self.parents
+            .iter()
+            .map(|parent| parent.before.len())
+            .collect()
+    }
+
+    /// Moves to the item at `index` of the list in focus
+    pub fn descend(&mut self, index: usize) -> bool {
+        let Packet::List(items) = &mut self.focus else {
+            return false;
+        };
+        if index >= items.len() {
+            return false;
+        }
+
+        let mut before = mem::take(items);
+        let mut after = before.split_off(index + 1);
+        after.reverse();
+        self.focus = before.pop().unwrap();
+        self.parents.push(Parent { before, after });
+        true
+    }
+
+    /// Moves to the list enclosing the focus
+    pub fn ascend(&mut self) -> bool {
+        let Some(Parent {
+            before: mut items,
+            after,
+        }) = self.parents.pop()
+        else {
+            return false;
+        };
+
+        let focus = mem::replace(&mut self.focus, Packet::List(Vec::new()));
+        items.push(focus);
+        items.extend(after.into_iter().rev());
+        self.focus = Packet::List(items);
+        true
+    }
+
+    /// Moves to the item after the focus
+    pub fn next_sibling(&mut self) -> bool {
+        let Some(Parent { before, after }) = self.parents.last_mut() else {
+            return false;
+        };
+        let Some(next) = after.pop() else {
+            return false;
+        };
+
+        before.push(mem::replace(&mut self.focus, next));
+        true
+    }
+
+    /// Moves to the item before the focus
+    pub fn prev_sibling(&mut self) -> bool {
+        let Some(Parent { before, after }) = self.parents.last_mut() else {
+            return false;
+        };
+        let Some(prev) = before.pop() else {
+            return false;
+        };
+
+        after.push(mem::replace(&mut self.focus, prev));
+        true
+    }
+
+    /// Puts a value in place of the focus, returning the value it replaced
+    pub fn replace(&mut self, packet: Packet<T>) -> Packet<T> {
+        mem::replace(&mut self.focus, packet)
+    }
+
+    /// Inserts a value before the focus, which stays where it is. The whole packet has
+    /// no siblings, so the value is handed back if the focus is the whole packet.
+    pub fn insert_before(&mut self, packet: Packet<T>) -> Result<(), Packet<T>> {
+        match self.parents.last_mut() {
+            Some(parent) => {
+                parent.before.push(packet);
+                Ok(())
+            }
+            None => Err(packet),
+        }
+    }
+
+    /// Inserts a value after the focus, which stays where it is. The whole packet has
+    /// no siblings, so the value is handed back if the focus is the whole packet.
+    pub fn insert_after(&mut self, packet: Packet<T>) -> Result<(), Packet<T>> {
+        match self.parents.last_mut() {
+            Some(parent) => {
+                parent.after.push(packet);
+                Ok(())
+            }
+            None => Err(packet),
+        }
+    }
+
+    /// Inserts a value at `index` of the list in focus, handing it back if the focus is
+    /// an integer or the index is past the end of the list
+    pub fn insert_child(&mut self, index: usize, packet: Packet<T>) -> Result<(), Packet<T>> {
+        match &mut self.focus {
+            Packet::List(items) if index <= items.len() => {
+                items.insert(index, packet);
+                Ok(())
+            }
+            _ => Err(packet),
+        }
+    }
+
+    /// Removes the focus from its list and returns it, moving to the next item, or the
+    /// previous item if it was the last, or the list if it is now empty. The whole
+    /// packet cannot be removed.
+    pub fn remove(&mut self) -> Option<Packet<T>> {
+        let Parent { before, after } = self.parents.last_mut()?;
+
+        let removed = match after.pop().or_else(|| before.pop()) {
+            Some(next) => mem::replace(&mut self.focus, next),
+            None => {
+                self.parents.pop();
+                mem::replace(&mut self.focus, Packet::List(Vec::new()))
+            }
+        };
+        Some(removed)
+    }
+
+    /// Puts the focus in a list of its own, keeping the new list in focus
+    pub fn wrap(&mut self) {
+        let focus = mem::replace(&mut self.focus, Packet::List(Vec::new()));
+        self.focus = Packet::List(vec![focus]);
+    }
+
+    /// Replaces a list in focus that holds exactly one item with that item, undoing
+    /// [`wrap`](Self::wrap)
+    pub fn unwrap(&mut self) -> bool {
+        match &mut self.focus {
+            Packet::List(items) if items.len() == 1 => {
+                self.focus = items.pop().unwrap();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves back out to the whole packet and returns it
+    pub fn into_packet(mut self) -> Packet<T> {
+        while self.ascend() {}
+        self.focus
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::{self, Write};
+use std::iter::Peekable;
+use std::slice;
+
+use crate::{write_spaces, Packet};
+
+impl<T: fmt::Display> fmt::Display for Packet<T> {
+    /// Writes the packet in the compact syntax it is parsed from, or with `{:#}`,
+    /// spread over several lines as by [`Packet::pretty`] with its default settings
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    ///
+    /// let packet: Packet = "[1, [2,3],\n[ ]]".parse().unwrap();
+    /// assert_eq!(packet.to_string(), "[1,[2,3],[]]");
+    /// assert!(packet.to_string().parse::<Packet>().unwrap().identical(&packet));
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.pretty())
+        } else {
+            write_compact(f, self)
+        }
+    }
+}
+
+impl<T: fmt::Display> Packet<T> {
+    /// Formats the packet over several lines, one item per line, except that any
+    /// list that fits in the remaining line width is kept on one line
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    ///
+    /// let packet: Packet = "[1,[2,3],[[4,5],6,7,8]]".parse().unwrap();
+    ///
+    /// assert_eq!(
+    ///     packet.pretty().width(20).to_string(),
+    ///     "[\n  1,\n  [2,3],\n  [[4,5],6,7,8]\n]"
+    /// );
+    /// assert_eq!(
+    ///     packet.pretty().width(14).indent(4).to_string(),
+    ///     "[\n    1,\n    [2,3],\n    [\n        [4,5],\n        6,\n        7,\n        8\n    ]\n]"
+    /// );
+    /// assert_eq!(format!("{packet:#}"), "[1,[2,3],[[4,5],6,7,8]]");
+    /// ```
+    pub fn pretty(&self) -> Pretty<'_, T> {
+        Pretty {
+            packet: self,
+            indent: 2,
+            width: 80,
+        }
+    }
+}
+
+/// A packet formatted over several lines. See [`Packet::pretty`].
+#[derive(Debug, Clone, Copy)]
+pub struct Pretty<'a, T = u8> {
+    packet: &'a Packet<T>,
+    indent: usize,
+    width: usize,
+}
+
+impl<T: fmt::Display> Pretty<'_, T> {
+    /// The number of spaces to indent each level of nesting by. Defaults to 2.
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    ///
+    /// let packet: Packet = "[[1]]".parse().unwrap();
+    /// let pretty = packet.pretty().width(0).indent(70_000).to_string();
+    /// assert_eq!(pretty.lines().map(str::len).max(), Some(140_001));
+    /// ```
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// The line width to keep lists within where possible. Defaults to 80.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    fn fits(&self, packet: &Packet<T>, level: usize, trailing_comma: bool) -> bool {
+        let used = level
+            .saturating_mul(self.indent)
+            .saturating_add(trailing_comma.into());
+        write_compact(&mut Budget(self.width.saturating_sub(used)), packet).is_ok()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Pretty<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let items = match self.packet {
+            Packet::List(v) if !self.fits(self.packet, 0, false) => v.iter().peekable(),
+            packet => return write_compact(f, packet),
+        };
+
+        f.write_str("[\n")?;
+        let mut stack: Vec<Peekable<slice::Iter<Packet<T>>>> = vec![items];
+
+        loop {
+            let level = stack.len();
+            let Some(items) = stack.last_mut() else {
+                return Ok(());
+            };
+
+            match items.next() {
+                Some(packet) => {
+                    let last = items.peek().is_none();
+                    write_spaces(f, level.saturating_mul(self.indent))?;
+
+                    match packet {
+                        Packet::List(v) if !self.fits(packet, level, !last) => {
+                            f.write_str("[\n")?;
+                            stack.push(v.iter().peekable());
+                        }
+                        _ => {
+                            write_compact(f, packet)?;
+                            f.write_str(if last { "\n" } else { ",\n" })?;
+                        }
+                    }
+                }
+                None => {
+                    stack.pop();
+                    write_spaces(f, (level - 1).saturating_mul(self.indent))?;
+                    f.write_str("]")?;
+
+                    if let Some(parent) = stack.last_mut() {
+                        f.write_str(if parent.peek().is_none() { "\n" } else { ",\n" })?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A writer that accepts only a limited number of bytes, to measure whether text fits
+struct Budget(usize);
+
+impl Write for Budget {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 = self.0.checked_sub(s.len()).ok_or(fmt::Error)?;
+        Ok(())
+    }
+}
+
+/// Writes a packet on one line, without any whitespace
+fn write_compact<T: fmt::Display>(f: &mut impl Write, packet: &Packet<T>) -> fmt::Result {
+    let items = match packet {
+        Packet::Int(n) => return write!(f, "{n}"),
+        Packet::List(v) => v.iter(),
+    };
+
+    f.write_char('[')?;
+    let mut stack = vec![(items, true)];
+
+    while let Some((items, first)) = stack.last_mut() {
+        match items.next() {
+            Some(packet) => {
+                if !*first {
+                    f.write_char(',')?;
+                }
+                *first = false;
+
+                match packet {
+                    Packet::Int(n) => write!(f, "{n}")?,
+                    Packet::List(v) => {
+                        f.write_char('[')?;
+                        stack.push((v.iter(), true));
+                    }
+                }
+            }
+            None => {
+                f.write_char(']')?;
+                stack.pop();
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::{path_string, Items, Packet, Pair};
+
+/// Why two packets are ordered the way they are, as found by [`Packet::explain_cmp`]
+#[derive(Debug, Clone)]
+pub struct Comparison<'a, T = u8> {
+    pub ordering: Ordering,
+    /// The index of the deciding item within each enclosing list, outermost first.
+    /// An integer promoted to a list is its own item 0.
+    pub path: Vec<usize>,
+    /// The values compared at `path`, or `None` for a side that ran out of items
+    pub left: Option<&'a Packet<T>>,
+    pub right: Option<&'a Packet<T>>,
+    /// Whether an integer was compared against a list on the way to `path`
+    pub promoted: bool,
+    /// Whether one side ran out of items, rather than two integers differing
+    pub by_length: bool,
+}
+
+impl<T: Ord> PartialEq for Comparison<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ordering == other.ordering
+            && self.path == other.path
+            && self.left == other.left
+            && self.right == other.right
+            && self.promoted == other.promoted
+            && self.by_length == other.by_length
+    }
+}
+
+impl<T: Ord> Eq for Comparison<'_, T> {}
+
+impl<T> Comparison<'_, T> {
+    /// The path written as indices, e.g. `[1][0][2]`
+    pub fn path_string(&self) -> String {
+        path_string(&self.path)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Comparison<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let at = if self.path.is_empty() {
+            "top level".to_string()
+        } else {
+            self.path_string()
+        };
+
+        match (self.ordering, self.left, self.right) {
+            (Ordering::Equal, ..) => write!(f, "equal")?,
+            (_, None, _) => write!(f, "less at {at}: left ran out of items")?,
+            (_, _, None) => write!(f, "greater at {at}: right ran out of items")?,
+            (ordering, Some(Packet::Int(l)), Some(Packet::Int(r))) => {
+                let (word, sign) = match ordering {
+                    Ordering::Less => ("less", '<'),
+                    _ => ("greater", '>'),
+                };
+                write!(f, "{word} at {at}: {l} {sign} {r}")?
+            }
+            (ordering, ..) => write!(f, "{ordering:?} at {at}")?,
+        }
+
+        if self.promoted {
+            write!(f, " (after promoting an integer to a list)")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Ord> Packet<T> {
+    /// Compares two packets like [`Ord::cmp`], also reporting where and why the
+    /// comparison was decided
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    /// use std::cmp::Ordering;
+    ///
+    /// let left: Packet = "[1,[2,[3,[4,[5,6,7]]]],8,9]".parse().unwrap();
+    /// let right: Packet = "[1,[2,[3,[4,[5,6,0]]]],8,9]".parse().unwrap();
+    /// let why = left.explain_cmp(&right);
+    /// assert_eq!(why.ordering, Ordering::Greater);
+    /// assert_eq!(why.path_string(), "[1][1][1][1][2]");
+    /// assert_eq!((why.left, why.right), (Some(&Packet::Int(7)), Some(&Packet::Int(0))));
+    /// assert!(!why.promoted && !why.by_length);
+    ///
+    /// let left: Packet = "[[1],[2,3,4]]".parse().unwrap();
+    /// let right: Packet = "[[1],4]".parse().unwrap();
+    /// let why = left.explain_cmp(&right);
+    /// assert_eq!(why.to_string(), "less at [1][0]: 2 < 4 (after promoting an integer to a list)");
+    ///
+    /// // A promotion only counts on the way to the deciding items, not in an earlier sibling
+    /// let left: Packet = "[[1],2]".parse().unwrap();
+    /// let right: Packet = "[1,3]".parse().unwrap();
+    /// let why = left.explain_cmp(&right);
+    /// assert!(!why.promoted);
+    /// assert_eq!(why.to_string(), "less at [1]: 2 < 3");
+    ///
+    /// let left: Packet = "[[[]]]".parse().unwrap();
+    /// let right: Packet = "[[]]".parse().unwrap();
+    /// let why = left.explain_cmp(&right);
+    /// assert_eq!((why.ordering, why.path, why.by_length), (Ordering::Greater, vec![0, 0], true));
+    /// assert_eq!(why.right, None);
+    /// ```
+    pub fn explain_cmp<'a>(&'a self, other: &'a Self) -> Comparison<'a, T> {
+        let decided = |ordering, path, left, right, promoted, by_length| Comparison {
+            ordering,
+            path,
+            left,
+            right,
+            promoted,
+            by_length,
+        };
+
+        if let (Self::Int(l), Self::Int(r)) = (self, other) {
+            return decided(l.cmp(r), vec![], Some(self), Some(other), false, false);
+        }
+
+        // Each frame records whether an integer was promoted on the way down to it
+        let promoted = matches!(self, Self::Int(_)) || matches!(other, Self::Int(_));
+        let mut stack = vec![(Items::of(self), Items::of(other), promoted)];
+        let mut path = vec![0];
+
+        while let Some((left, right, promoted)) = stack.last_mut() {
+            let promoted = *promoted;
+            match (left.next(), right.next()) {
+                (None, None) => {
+                    stack.pop();
+                    path.pop();
+                    if let Some(i) = path.last_mut() {
+                        *i += 1;
+                    }
+                }
+                (None, r @ Some(_)) => {
+                    return decided(Ordering::Less, path, None, r, promoted, true);
+                }
+                (l @ Some(_), None) => {
+                    return decided(Ordering::Greater, path, l, None, promoted, true);
+                }
+                (Some(l @ Self::Int(a)), Some(r @ Self::Int(b))) => match a.cmp(b) {
+                    Ordering::Equal => *path.last_mut().unwrap() += 1,
+                    ordering => return decided(ordering, path, Some(l), Some(r), promoted, false),
+                },
+                (Some(l), Some(r)) => {
+                    let promoted =
+                        promoted || matches!(l, Self::Int(_)) || matches!(r, Self::Int(_));
+                    stack.push((Items::of(l), Items::of(r), promoted));
+                    path.push(0);
+                }
+            }
+        }
+
+        decided(
+            Ordering::Equal,
+            vec![],
+            Some(self),
+            Some(other),
+            false,
+            false,
+        )
+    }
+}
+
+impl<T: Ord> Pair<T> {
+    /// Explains why this pair is or is not in order. See [`Packet::explain_cmp`].
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Pair;
+    ///
+    /// let pair = Pair::new("[7,7,7,7]\n[7,7,7]");
+    /// assert_eq!(pair.explain().to_string(), "greater at [3]: right ran out of items");
+    /// ```
+    pub fn explain(&self) -> Comparison<'_, T> {
+        self.left.explain_cmp(&self.right)
+    }
+}
@@ -0,0 +1,202 @@
+use std::ops::RangeInclusive;
+
+use crate::{Packet, PacketInt, Pair};
+
+/// How many items [`PacketGenerator`] puts in each list that is not empty
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListLength {
+    /// Any length from `min` to `max` inclusive, equally likely
+    Uniform { min: usize, max: usize },
+    /// Each further item is added with a fixed probability, giving lengths that
+    /// average `mean` but are occasionally much longer
+    Geometric { mean: f64 },
+}
+
+/// Generates random packets and puzzle input from a seed
+///
+/// The same seed and settings always generate the same packets on every platform, so a
+/// seed and the settings are enough to reproduce a bug report.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{sum_correct, ListLength, Packet, PacketGenerator};
+///
+/// let mut generator = PacketGenerator::new(13)
+///     .max_depth(3)
+///     .list_len(ListLength::Uniform { min: 1, max: 4 })
+///     .ints(-5..=5)
+///     .empty_list_probability(0.2);
+///
+/// let packet: Packet<i32> = generator.packet();
+/// assert!(packet.to_string().starts_with('['));
+///
+/// let input = PacketGenerator::new(7).input(100);
+/// assert_eq!(input, PacketGenerator::new(7).input(100));
+/// assert_eq!(input.lines().count(), 299);
+/// assert!(sum_correct(&input).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PacketGenerator {
+    state: u64,
+    max_depth: usize,
+    list_len: ListLength,
+    ints: RangeInclusive<i64>,
+    empty_list_probability: f64,
+    list_probability: f64,
+}
+
+impl PacketGenerator {
+    /// A generator with settings resembling the puzzle input: lists nested at most 4
+    /// deep, holding 1 to 5 items or occasionally none, and integers from 0 to 10
+    pub fn new(seed: u64) -> Self {
+        PacketGenerator {
+            state: seed,
+            max_depth: 4,
+            list_len: ListLength::Uniform { min: 1, max: 5 },
+            ints: 0..=10,
+            empty_list_probability: 0.1,
+            list_probability: 0.3,
+        }
+    }
+
+    /// The deepest lists may be nested, counting the outermost list as 1
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth.max(1);
+        self
+    }
+
+    /// How many items a list holds when it is not empty
+    pub fn list_len(mut self, list_len: ListLength) -> Self {
+        self.list_len = list_len;
+        self
+    }
+
+    /// The range integers are drawn from, equally likely. An empty range gives only
+    /// its start.
+    pub fn ints(mut self, ints: RangeInclusive<i64>) -> Self {
+        self.ints = ints;
+        self
+    }
+
+    /// The probability that a list is empty, whatever its length would otherwise be
+    pub fn empty_list_probability(mut self, probability: f64) -> Self {
+        self.empty_list_probability = probability;
+        self
+    }
+
+    /// The probability that an item not at the deepest level is a list rather than an
+    /// integer
+    pub fn list_probability(mut self, probability: f64) -> Self {
+        self.list_probability = probability;
+        self
+    }
+
+    /// Generates a packet, which like those in the puzzle is always a list
+    /// # Panics
+    /// If an integer in the range given to [`ints`](Self::ints) does not fit in `T`.
+    pub fn packet<T: PacketInt>(&mut self) -> Packet<T> {
+        // Each open list, with the number of items it still needs
+        let mut open: Vec<(usize, Vec<Packet<T>>)> = vec![(self.length(), Vec::new())];
+
+        loop {
+            let depth = open.len();
+            let (needed, _) = open.last_mut().unwrap();
+
+            if *needed == 0 {
+                let (_, items) = open.pop().unwrap();
+                match open.last_mut() {
+                    Some((_, parent)) => parent.push(Packet::List(items)),
+                    None => return Packet::List(items),
+                }
+                continue;
+            }
+            *needed -= 1;
+
+            if depth < self.max_depth && self.chance(self.list_probability) {
+                open.push((self.length(), Vec::new()));
+            } else {
+                let n = self.int();
+                let n = T::from_i64(n).unwrap_or_else(|| {
+                    panic!("generated integer {n} does not fit in the packet's integer type")
+                });
+                open.last_mut().unwrap().1.push(Packet::Int(n));
+            }
+        }
+    }
+
+    /// Generates a pair of packets
+    pub fn pair<T: PacketInt>(&mut self) -> Pair<T> {
+        Pair {
+            left: self.packet(),
+            right: self.packet(),
+        }
+    }
+
+    /// Generates puzzle input holding the given number of pairs, separated by blank
+    /// lines
+    pub fn input(&mut self, pairs: usize) -> String {
+        let mut input = String::new();
+
+        for i in 0..pairs {
+            if i > 0 {
+                input.push('\n');
+            }
+            let pair: Pair<i64> = self.pair();
+            input += &format!("{}\n{}\n", pair.left(), pair.right());
+        }
+
+        input
+    }
+
+    /// The next number from a SplitMix64 sequence
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns true with the given probability
+    fn chance(&mut self, probability: f64) -> bool {
+        // The top 53 bits make a float in [0, 1) exactly
+        ((self.next() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    /// A number from 0 to `n` inclusive
+    fn up_to(&mut self, n: u64) -> u64 {
+        match n.checked_add(1) {
+            Some(count) => ((self.next() as u128 * count as u128) >> 64) as u64,
+            None => self.next(),
+        }
+    }
+
+    fn int(&mut self) -> i64 {
+        let (min, max) = (*self.ints.start(), *self.ints.end());
+        if max < min {
+            return min;
+        }
+
+        min.wrapping_add(self.up_to(max.abs_diff(min)) as i64)
+    }
+
+    fn length(&mut self) -> usize {
+        if self.chance(self.empty_list_probability) {
+            return 0;
+        }
+
+        match self.list_len {
+            ListLength::Uniform { min, max } => {
+                min + self.up_to(max.saturating_sub(min) as u64) as usize
+            }
+            ListLength::Geometric { mean } => {
+                let more = mean / (mean + 1.0);
+                let mut len = 0;
+                while self.chance(more) {
+                    len += 1;
+                }
+                len
+            }
+        }
+    }
+}
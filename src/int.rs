@@ -0,0 +1,194 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// An integer type that can be held by [`Packet::Int`](crate::Packet::Int)
+///
+/// Implemented for all the primitive integer types and for [`BigInt`]. Unsigned types
+/// reject negative numbers, and every type rejects numbers it cannot represent, so a
+/// value is never silently truncated.
+pub trait PacketInt: Ord + Clone + fmt::Debug + fmt::Display {
+    /// Converts a non-empty run of ASCII digits, negated if `negative`, or returns
+    /// `None` if the number is out of range
+    fn from_digits(negative: bool, digits: &[u8]) -> Option<Self>;
+
+    /// Converts a `u64`, or returns `None` if it is out of range
+    fn from_u64(n: u64) -> Option<Self> {
+        Self::from_digits(false, n.to_string().as_bytes())
+    }
+
+    /// Converts an `i64`, or returns `None` if it is out of range
+    fn from_i64(n: i64) -> Option<Self> {
+        Self::from_digits(n < 0, n.unsigned_abs().to_string().as_bytes())
+    }
+}
+
+macro_rules! impl_packet_int {
+    ($($t:ty),*) => {$(
+        impl PacketInt for $t {
+            fn from_digits(negative: bool, digits: &[u8]) -> Option<Self> {
+                digits.iter().try_fold(0 as $t, |n, d| {
+                    let d = <$t>::try_from(d - b'0').ok()?;
+                    let n = n.checked_mul(10)?;
+                    if negative {
+                        n.checked_sub(d)
+                    } else {
+                        n.checked_add(d)
+                    }
+                })
+            }
+
+            fn from_u64(n: u64) -> Option<Self> {
+                n.try_into().ok()
+            }
+
+            fn from_i64(n: i64) -> Option<Self> {
+                n.try_into().ok()
+            }
+        }
+    )*};
+}
+
+impl_packet_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// An integer of any size, for packets whose integers do not fit in a primitive type
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{BigInt, Packet};
+///
+/// let small: Packet<BigInt> = "[-5,18446744073709551616]".parse().unwrap();
+/// let big: Packet<BigInt> = "[-5,18446744073709551617]".parse().unwrap();
+///
+/// assert!(small < big);
+/// assert_eq!(small.to_string(), "[-5,18446744073709551616]");
+/// assert!(BigInt::from(-5i64) < BigInt::from(3u64));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    /// ASCII digits, most significant first, with no leading zeros. Zero has no
+    /// digits and is never negative.
+    digits: Box<[u8]>,
+}
+
+impl BigInt {
+    fn magnitude_cmp(&self, other: &Self) -> Ordering {
+        self.digits
+            .len()
+            .cmp(&other.digits.len())
+            .then_with(|| self.digits.cmp(&other.digits))
+    }
+}
+
+impl PacketInt for BigInt {
+    fn from_digits(negative: bool, digits: &[u8]) -> Option<Self> {
+        let start = digits
+            .iter()
+            .position(|&d| d != b'0')
+            .unwrap_or(digits.len());
+        let digits: Box<[u8]> = digits[start..].into();
+
+        Some(BigInt {
+            negative: negative && !digits.is_empty(),
+            digits,
+        })
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    /// Parses an optional sign followed by decimal digits, with no surrounding whitespace
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::BigInt;
+    ///
+    /// let n: BigInt = "-340282366920938463463374607431768211456".parse().unwrap();
+    /// assert_eq!(n.to_string(), "-340282366920938463463374607431768211456");
+    /// assert_eq!("+007".parse::<BigInt>().unwrap(), BigInt::from(7u64));
+    /// assert_eq!("-0".parse::<BigInt>().unwrap(), BigInt::from(0u64));
+    ///
+    /// assert_eq!("12a".parse::<BigInt>().unwrap_err().offset, 2);
+    /// assert_eq!("-".parse::<BigInt>().unwrap_err().offset, 1);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.as_bytes() {
+            [b'-', rest @ ..] => (true, rest),
+            [b'+', rest @ ..] => (false, rest),
+            digits => (false, digits),
+        };
+        let sign = s.len() - digits.len();
+
+        if let Some(i) = digits.iter().position(|d| !d.is_ascii_digit()) {
+            return Err(ParseBigIntError { offset: sign + i });
+        }
+        if digits.is_empty() {
+            return Err(ParseBigIntError { offset: sign });
+        }
+
+        Ok(Self::from_digits(negative, digits).unwrap())
+    }
+}
+
+/// A string that is not a decimal integer, as found by parsing a [`BigInt`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseBigIntError {
+    /// The byte offset of the first character that is not part of the integer
+    pub offset: usize,
+}
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid integer at byte {}", self.offset)
+    }
+}
+
+impl Error for ParseBigIntError {}
+
+impl From<u64> for BigInt {
+    fn from(n: u64) -> Self {
+        Self::from_u64(n).unwrap()
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        Self::from_i64(n).unwrap()
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.magnitude_cmp(other),
+            (true, true) => other.magnitude_cmp(self),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.digits.is_empty() {
+            return f.pad_integral(true, "", "0");
+        }
+
+        // The digits are always ASCII
+        let digits = std::str::from_utf8(&self.digits).unwrap();
+        f.pad_integral(!self.negative, "", digits)
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,141 @@
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+
+use crate::{path_string, JsonType, Packet, PacketInt};
+
+/// The reasons a [`serde_json::Value`] can fail to convert into a packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromJsonError {
+    /// A number that does not fit in the packet's integer type
+    IntegerOverflow { path: Vec<usize> },
+    /// A JSON value other than a list or an integer
+    UnsupportedJsonType { found: JsonType, path: Vec<usize> },
+}
+
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (what, path) = match self {
+            Self::IntegerOverflow { path } => ("integer out of range".to_string(), path),
+            Self::UnsupportedJsonType { found, path } => (format!("unsupported {found}"), path),
+        };
+
+        write!(f, "{what} at {}", path_string(path))
+    }
+}
+
+impl Error for FromJsonError {}
+
+impl<T: PacketInt> TryFrom<&Value> for Packet<T> {
+    type Error = FromJsonError;
+
+    /// Converts the value without recursing, however deeply it is nested
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::{FromJsonError, JsonType, Packet};
+    /// use serde_json::json;
+    ///
+    /// let packet: Packet = Packet::try_from(&json!([1, [2]])).unwrap();
+    /// assert_eq!(packet, "[1,[2]]".parse().unwrap());
+    /// assert_eq!(
+    ///     Packet::<u8>::try_from(&json!([1, [null]])),
+    ///     Err(FromJsonError::UnsupportedJsonType { found: JsonType::Null, path: vec![1, 0] })
+    /// );
+    /// assert_eq!(
+    ///     Packet::<u8>::try_from(&json!([[], [1, [2, 300]]])),
+    ///     Err(FromJsonError::IntegerOverflow { path: vec![1, 1, 1] })
+    /// );
+    /// ```
+    fn try_from(val: &Value) -> Result<Self, Self::Error> {
+        from_json_val(val)
+    }
+}
+
+impl<T: Clone + Into<Value>> From<&Packet<T>> for Value {
+    /// Converts the packet without recursing, though serde_json itself drops and writes
+    /// a `Value` recursively, so a very deep one can still overflow the stack there
+    fn from(packet: &Packet<T>) -> Self {
+        let items = match packet {
+            Packet::Int(n) => return n.clone().into(),
+            Packet::List(items) => items,
+        };
+        // Each frame holds the items still to convert and the values converted so far
+        let mut stack = vec![(items.iter(), Vec::with_capacity(items.len()))];
+
+        loop {
+            let (source, converted) = stack.last_mut().unwrap();
+
+            match source.next() {
+                Some(Packet::Int(n)) => converted.push(n.clone().into()),
+                Some(Packet::List(v)) => stack.push((v.iter(), Vec::with_capacity(v.len()))),
+                None => {
+                    let (_, done) = stack.pop().unwrap();
+                    match stack.last_mut() {
+                        Some((_, converted)) => converted.push(Value::Array(done)),
+                        None => return Value::Array(done),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn from_json_val<T: PacketInt>(val: &Value) -> Result<Packet<T>, FromJsonError> {
+    let items = match val {
+        Value::Array(items) => items,
+        val => return from_json_int(val, &[]).map(Packet::Int),
+    };
+    // Each frame holds the values still to convert and the items converted so far, and
+    // `path` the index of each list being converted within its parent
+    let mut stack = vec![(items.iter(), Vec::with_capacity(items.len()))];
+    let mut path = Vec::new();
+
+    loop {
+        let (source, converted) = stack.last_mut().unwrap();
+
+        match source.next() {
+            Some(Value::Array(v)) => {
+                path.push(converted.len());
+                stack.push((v.iter(), Vec::with_capacity(v.len())));
+            }
+            Some(val) => {
+                path.push(converted.len());
+                converted.push(Packet::Int(from_json_int(val, &path)?));
+                path.pop();
+            }
+            None => {
+                let (_, done) = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some((_, converted)) => {
+                        path.pop();
+                        converted.push(Packet::List(done));
+                    }
+                    None => return Ok(Packet::List(done)),
+                }
+            }
+        }
+    }
+}
+
+/// Converts any value but an array, which `from_json_val` handles itself
+fn from_json_int<T: PacketInt>(val: &Value, path: &[usize]) -> Result<T, FromJsonError> {
+    let unsupported = |found| FromJsonError::UnsupportedJsonType {
+        found,
+        path: path.to_vec(),
+    };
+
+    match val {
+        Value::Number(n) if n.is_u64() || n.is_i64() => n
+            .as_u64()
+            .map_or_else(|| T::from_i64(n.as_i64().unwrap()), T::from_u64)
+            .ok_or_else(|| FromJsonError::IntegerOverflow {
+                path: path.to_vec(),
+            }),
+        Value::Number(_) => Err(unsupported(JsonType::Float)),
+        Value::String(_) => Err(unsupported(JsonType::String)),
+        Value::Object(_) => Err(unsupported(JsonType::Object)),
+        Value::Bool(_) => Err(unsupported(JsonType::Bool)),
+        Value::Null => Err(unsupported(JsonType::Null)),
+        Value::Array(_) => unreachable!("arrays are converted by from_json_val"),
+    }
+}
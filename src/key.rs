@@ -0,0 +1,260 @@
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::slice;
+
+use crate::{Packet, PacketInt};
+
+// A sort key lists the integers of a packet in order, each as a tag byte and its
+// decimal digits, and ends each integer and each list with a `0` byte and its depth.
+// Lists are opened implicitly by the depth of their first item. An integer promoted to
+// a list therefore looks just like an integer, except for the depth it ends at: a deeper
+// end means the list carries on past where the promoted integer stops, so it is
+// greater. Only a list holding nothing but a chain of one-item lists down to an
+// integer equals that integer, and it is written as the integer itself.
+
+const NEGATIVE: u8 = 1;
+const NON_NEGATIVE: u8 = 2;
+const END: u8 = 0;
+
+/// A byte string that is not a sort key made by [`Packet::to_sort_key`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKeyError {
+    /// The byte offset of the first problem in the key
+    pub offset: usize,
+}
+
+impl fmt::Display for SortKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "malformed sort key at byte {}", self.offset)
+    }
+}
+
+impl Error for SortKeyError {}
+
+impl<T: fmt::Display> Packet<T> {
+    /// Encodes the packet as bytes that sort, compared byte by byte, exactly as the
+    /// packet does
+    ///
+    /// Packets that are equal, like `5` and `[[5]]`, have the same key, so
+    /// [`Packet::from_sort_key`] gives back a packet equal to this one but not
+    /// necessarily identical to it. Integers are encoded from their decimal form.
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    ///
+    /// let packets = ["[[1],[2,3,4]]", "[[1],4]", "[[8,7,6]]", "[9]", "[]", "[[]]", "[3]"]
+    ///     .map(|s| s.parse::<Packet>().unwrap());
+    ///
+    /// for left in &packets {
+    ///     for right in &packets {
+    ///         assert_eq!(left.to_sort_key().cmp(&right.to_sort_key()), left.cmp(right));
+    ///     }
+    /// }
+    /// assert_eq!(Packet::<u8>::Int(5).to_sort_key(), "[[5]]".parse::<Packet>().unwrap().to_sort_key());
+    /// ```
+    pub fn to_sort_key(&self) -> Vec<u8> {
+        let mut key = Vec::new();
+        let mut digits = String::new();
+        // Each open list, with its items still to be encoded and its depth
+        let mut stack: Vec<(slice::Iter<Packet<T>>, usize)> = Vec::new();
+        let mut next = Some((self, 0));
+
+        loop {
+            if let Some((packet, depth)) = next.take() {
+                // Skip down any chain of one-item lists, which equal an integer at the end
+                let (mut bottom, mut links) = (packet, 0);
+                while let Packet::List(items) = bottom {
+                    match items.as_slice() {
+                        [item] => (bottom, links) = (item, links + 1),
+                        _ => break,
+                    }
+                }
+
+                match bottom {
+                    Packet::Int(n) => {
+                        digits.clear();
+                        // Writing to a `String` cannot fail
+                        write!(digits, "{n}").unwrap();
+                        push_int(&mut key, &digits);
+                        push_end(&mut key, depth);
+                    }
+                    Packet::List(items) => {
+                        // The one-item lists on the way down have no items left to
+                        // encode once the list below them is done
+                        stack.extend((0..links).map(|i| ([].iter(), depth + i)));
+                        stack.push((items.iter(), depth + links));
+                    }
+                }
+            }
+
+            let Some((items, depth)) = stack.last_mut() else {
+                return key;
+            };
+            match items.next() {
+                Some(item) => next = Some((item, *depth + 1)),
+                None => {
+                    push_end(&mut key, *depth);
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<T: PacketInt> Packet<T> {
+    /// Decodes a key made by [`Packet::to_sort_key`]
+    ///
+    /// The packet returned has every list that holds only a chain of one-item lists
+    /// down to an integer replaced by that integer.
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    ///
+    /// let packet: Packet<i32> = "[[1,-20],[[300]],[]]".parse().unwrap();
+    /// let decoded = Packet::from_sort_key(&packet.to_sort_key()).unwrap();
+    /// assert_eq!(decoded.to_string(), "[[1,-20],300,[]]");
+    /// assert_eq!(decoded, packet);
+    ///
+    /// let err = Packet::<u8>::from_sort_key(&[2, 1, 1, b'5']).unwrap_err();
+    /// assert_eq!(err.offset, 4);
+    /// assert!(Packet::<u8>::from_sort_key(&packet.to_sort_key()).is_err());
+    /// ```
+    pub fn from_sort_key(key: &[u8]) -> Result<Self, SortKeyError> {
+        let mut reader = KeyReader { key, pos: 0 };
+        // The items so far of each open list, outermost first
+        let mut open: Vec<Vec<Packet<T>>> = Vec::new();
+
+        loop {
+            let start = reader.pos;
+            let error = SortKeyError { offset: start };
+
+            let item = match reader.byte().ok_or(error)? {
+                tag @ (NEGATIVE | NON_NEGATIVE) => {
+                    let n = reader.int(tag == NEGATIVE).ok_or(error)?;
+                    let depth = reader.end().ok_or(SortKeyError { offset: reader.pos })?;
+                    // Every open list needs its own end, so no depth can exceed the length
+                    if depth > key.len() || open.len() > depth {
+                        return Err(error);
+                    }
+                    open.resize_with(depth, Vec::new);
+                    Packet::Int(n)
+                }
+                END => {
+                    let depth = reader.number().ok_or(error)?;
+                    if depth > key.len() || open.len() > depth + 1 {
+                        return Err(error);
+                    }
+                    open.resize_with(depth + 1, Vec::new);
+                    Packet::List(open.pop().unwrap())
+                }
+                _ => return Err(error),
+            };
+
+            match open.last_mut() {
+                Some(items) => items.push(item),
+                None if reader.pos == key.len() => return Ok(item),
+                None => return Err(SortKeyError { offset: reader.pos }),
+            }
+        }
+    }
+}
+
+/// Appends an integer given in decimal, with an optional `-`. Negative integers are
+/// complemented so that larger magnitudes sort first.
+fn push_int(key: &mut Vec<u8>, digits: &str) {
+    let (tag, digits) = match digits.strip_prefix('-') {
+        Some(digits) => (NEGATIVE, digits),
+        None => (NON_NEGATIVE, digits),
+    };
+
+    key.push(tag);
+    let start = key.len();
+    push_number(key, digits.len());
+    key.extend_from_slice(digits.as_bytes());
+
+    if tag == NEGATIVE {
+        for b in &mut key[start..] {
+            *b = !*b;
+        }
+    }
+}
+
+/// Appends the end of an integer or list at the given depth
+fn push_end(key: &mut Vec<u8>, depth: usize) {
+    key.push(END);
+    push_number(key, depth);
+}
+
+/// Appends a number as its length in bytes followed by its big-endian bytes, so that
+/// larger numbers sort later
+fn push_number(key: &mut Vec<u8>, n: usize) {
+    let bytes = n.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+
+    key.push((bytes.len() - skip) as u8);
+    key.extend_from_slice(&bytes[skip..]);
+}
+
+struct KeyReader<'a> {
+    key: &'a [u8],
+    pos: usize,
+}
+
+impl KeyReader<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let b = *self.key.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&[u8]> {
+        let bytes = self.key.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    /// Reads a number written by `push_number`, complemented if `negative`
+    fn number_as(&mut self, negative: bool) -> Option<usize> {
+        let flip = |b: u8| if negative { !b } else { b };
+
+        let len = flip(self.byte()?) as usize;
+        let bytes = self.bytes(len)?;
+        if len > size_of::<usize>() || bytes.first().is_some_and(|&b| flip(b) == 0) {
+            return None;
+        }
+
+        Some(bytes.iter().fold(0, |n, &b| n << 8 | flip(b) as usize))
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        self.number_as(false)
+    }
+
+    /// Reads an integer after its tag
+    fn int<T: PacketInt>(&mut self, negative: bool) -> Option<T> {
+        let len = self.number_as(negative)?;
+        let digits: Vec<u8> = self
+            .bytes(len)?
+            .iter()
+            .map(|&b| if negative { !b } else { b })
+            .collect();
+
+        let canonical = match digits.as_slice() {
+            [] => false,
+            [b'0'] => !negative,
+            [first, ..] => *first != b'0',
+        };
+        if !canonical || !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        T::from_digits(negative, &digits)
+    }
+
+    /// Reads the end of an integer and returns its depth
+    fn end(&mut self) -> Option<usize> {
+        match self.byte()? {
+            END => self.number(),
+            _ => None,
+        }
+    }
+}
@@ -1,14 +1,73 @@
-use serde_json::Value;
 use std::cmp::Ordering;
-use std::iter::zip;
+use std::fmt;
 use std::str::FromStr;
 
-pub struct Pair {
-    left: Packet,
-    right: Packet,
+mod cursor;
+mod display;
+mod explain;
+mod flat;
+mod generate;
+mod int;
+#[cfg(feature = "serde_json")]
+mod json;
+mod key;
+mod macros;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod parse;
+mod query;
+mod reader;
+#[cfg(feature = "serde")]
+mod serde;
+mod shrink;
+mod sort;
+#[cfg(feature = "proptest")]
+mod strategy;
+
+pub use cursor::PacketCursor;
+pub use display::Pretty;
+pub use explain::Comparison;
+pub use flat::FlatPacket;
+pub use generate::{ListLength, PacketGenerator};
+pub use int::{BigInt, PacketInt, ParseBigIntError};
+#[cfg(feature = "serde_json")]
+pub use json::FromJsonError;
+pub use key::SortKeyError;
+#[cfg(feature = "parallel")]
+pub use parallel::{parse_pairs_par, sum_correct_par, sum_correct_par_as};
+pub use parse::{JsonType, PacketParseError, PairErrorKind, PairParseError, Position};
+pub use query::{Match, Query, QueryParseError};
+pub use reader::{PairReader, ReadError};
+pub use shrink::shrink;
+pub use sort::{ExternalSort, SortError};
+#[cfg(feature = "proptest")]
+pub use strategy::{packet_strategy, pair_strategy};
+
+#[derive(Debug, Clone)]
+pub struct Pair<T = u8> {
+    left: Packet<T>,
+    right: Packet<T>,
 }
 
-impl Pair {
+impl<T: Ord> PartialEq for Pair<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.left == other.left && self.right == other.right
+    }
+}
+
+impl<T: Ord> Eq for Pair<T> {}
+
+impl<T> Pair<T> {
+    pub fn left(&self) -> &Packet<T> {
+        &self.left
+    }
+
+    pub fn right(&self) -> &Packet<T> {
+        &self.right
+    }
+}
+
+impl<T: Ord> Pair<T> {
     /// # Examples
     /// ```
     /// use advent_of_code_2022_13::{Pair, Packet};
@@ -25,104 +84,587 @@ impl Pair {
     pub fn is_in_order(&self) -> bool {
         self.left <= self.right
     }
+}
 
+impl Pair {
+    /// Parses a pair, panicking if it is malformed. See [`Pair::try_from_str`].
     pub fn new(instring: &str) -> Self {
-        let lines: Vec<_> = instring.lines().collect();
+        Self::try_from_str(instring).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Parses a block of exactly two lines, each holding one packet. To use a different
+    /// integer type, parse a [`Pair<T>`] with [`str::parse`].
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::{Pair, PairErrorKind, PacketParseError};
+    ///
+    /// assert!(Pair::try_from_str("[1]\n[2]").unwrap().is_in_order());
+    ///
+    /// let err = Pair::try_from_str("[1]").unwrap_err();
+    /// assert_eq!((err.line, err.kind), (1, PairErrorKind::MissingRight));
+    ///
+    /// let err = Pair::try_from_str("[1]\n[2]\n[3]").unwrap_err();
+    /// assert_eq!((err.line, err.kind), (3, PairErrorKind::ExtraLines));
+    ///
+    /// let err = "[1]\n[2,]".parse::<Pair>().unwrap_err();
+    /// assert_eq!(err.line, 2);
+    /// assert!(matches!(err.kind, PairErrorKind::InvalidPacket(PacketParseError::UnexpectedToken { .. })));
+    /// assert_eq!(err.to_string(), "pair 1: unexpected `]` at line 2, column 4, expected a list or an integer");
+    ///
+    /// let err = Pair::try_from_str("[1]\n[256]").unwrap_err();
+    /// assert!(matches!(err.kind, PairErrorKind::InvalidPacket(PacketParseError::IntegerOverflow { .. })));
+    /// assert!("[1]\n[256]".parse::<Pair<u32>>().unwrap().is_in_order());
+    /// ```
+    pub fn try_from_str(instring: &str) -> Result<Self, PairParseError> {
+        instring.parse()
+    }
+}
+
+impl<T: PacketInt> Pair<T> {
+    /// Parses a pair directly from bytes, without requiring them to be valid UTF-8
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Pair;
+    ///
+    /// let pair: Pair = Pair::from_bytes(b"[1]\r\n[2]\r\n").unwrap();
+    /// assert!(pair.is_in_order());
+    /// assert_eq!(Pair::<u8>::from_bytes(b"[1]\n[\xff]").unwrap_err().line, 2);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PairParseError> {
+        let error = |line, kind| PairParseError {
+            block: 1,
+            line,
+            kind,
+        };
+        let packet = |i: usize, line: &[u8]| {
+            Packet::from_bytes(line).map_err(|e| {
+                let start = line.as_ptr() as usize - bytes.as_ptr() as usize;
+                error(i + 1, PairErrorKind::InvalidPacket(e.shifted(i, start)))
+            })
+        };
+
+        let mut lines = lines(bytes).enumerate();
+
+        let Some((i, left)) = lines.next() else {
+            return Err(error(1, PairErrorKind::MissingLeft));
+        };
+        let left = packet(i, left)?;
 
-        Pair {
-            left: lines[0].parse().unwrap(),
-            right: lines[1].parse().unwrap(),
+        let Some((i, right)) = lines.next() else {
+            return Err(error(1, PairErrorKind::MissingRight));
+        };
+        let right = packet(i, right)?;
+
+        match lines.next() {
+            Some((i, _)) => Err(error(i + 1, PairErrorKind::ExtraLines)),
+            None => Ok(Pair { left, right }),
+        }
+    }
+}
+
+impl<T: PacketInt> FromStr for Pair<T> {
+    type Err = PairParseError;
+
+    fn from_str(instring: &str) -> Result<Self, Self::Err> {
+        Self::from_bytes(instring.as_bytes())
+    }
+}
+
+/// Splits bytes into lines the way [`str::lines`] does. A `\r` before a `\n` is kept,
+/// as packets ignore whitespace anyway.
+fn lines(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let count = if bytes.is_empty() { 0 } else { usize::MAX };
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+
+    bytes.split(|&b| b == b'\n').take(count)
+}
+
+/// Writes a path of indices into a packet, outermost first, e.g. `[1][0][2]`
+pub(crate) fn path_string(path: &[usize]) -> String {
+    path.iter().map(|i| format!("[{i}]")).collect()
+}
+
+/// A packet: an integer, or a list of packets
+///
+/// The integer type defaults to `u8`, which is enough for the puzzle input, but can be
+/// any [`PacketInt`].
+///
+/// Packets implement [`Drop`] so that deep ones are freed without recursing, which
+/// means a packet cannot be destructured by value, as in `let Packet::List(v) = p`.
+/// Use [`Packet::into_items`] to take ownership of the items instead.
+pub enum Packet<T = u8> {
+    Int(T),
+    List(Vec<Packet<T>>),
+}
+
+// Every traversal of a packet below keeps its own stack on the heap rather than
+// recursing, so that arbitrarily deep packets cannot overflow the call stack.
+
+/// The items of a list, or an integer viewed as a single-item list
+pub(crate) enum Items<'a, T> {
+    List(std::slice::Iter<'a, Packet<T>>),
+    Int(Option<&'a Packet<T>>),
+}
+
+impl<'a, T> Items<'a, T> {
+    pub(crate) fn of(packet: &'a Packet<T>) -> Self {
+        match packet {
+            Packet::Int(_) => Items::Int(Some(packet)),
+            Packet::List(v) => Items::List(v.iter()),
         }
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
-pub enum Packet {
-    Int(u8),
-    List(Vec<Packet>),
+impl<'a, T> Iterator for Items<'a, T> {
+    type Item = &'a Packet<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Items::List(iter) => iter.next(),
+            Items::Int(int) => int.take(),
+        }
+    }
 }
 
-impl PartialOrd for Packet {
+impl<T: Ord> Ord for Packet<T> {
+    /// Compares packets by the puzzle rules: integers by value, lists item by item and
+    /// then by length, and an integer against a list as if it were a one-item list.
+    /// The integer is only ever borrowed, so comparison never clones or allocates a packet.
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    /// use std::collections::BTreeSet;
+    ///
+    /// let mut packets: Vec<Packet> = ["[[2]]", "[3]", "[]", "[[1],4]", "[[2]]"]
+    ///     .iter()
+    ///     .map(|s| s.parse().unwrap())
+    ///     .collect();
+    /// packets.sort();
+    /// packets.dedup();
+    /// assert_eq!(packets, ["[]", "[[1],4]", "[[2]]", "[3]"].map(|s| s.parse().unwrap()));
+    ///
+    /// let set: BTreeSet<Packet> = packets.into_iter().collect();
+    /// assert_eq!(set.first(), Some(&"[]".parse().unwrap()));
+    ///
+    /// let deep = |n| "[".repeat(n) + &"]".repeat(n);
+    /// let shallow = deep(99_999).parse::<Packet>().unwrap();
+    /// let deeper = deep(100_000).parse::<Packet>().unwrap();
+    /// assert!(shallow < deeper);
+    /// ```
+    fn cmp(&self, other: &Self) -> Ordering {
+        if let (Self::Int(left), Self::Int(right)) = (self, other) {
+            return left.cmp(right);
+        }
+
+        // The innermost pair of lists being compared is kept out of the stack, so
+        // comparing flat lists never allocates
+        let mut current = (Items::of(self), Items::of(other));
+        let mut stack = Vec::new();
+
+        loop {
+            match (current.0.next(), current.1.next()) {
+                (None, None) => match stack.pop() {
+                    Some(outer) => current = outer,
+                    None => return Ordering::Equal,
+                },
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(Self::Int(l)), Some(Self::Int(r))) => match l.cmp(r) {
+                    Ordering::Equal => (),
+                    decided => return decided,
+                },
+                (Some(l), Some(r)) => {
+                    let inner = (Items::of(l), Items::of(r));
+                    stack.push(std::mem::replace(&mut current, inner));
+                }
+            }
+        }
+    }
+}
+
+impl<T: Ord> PartialOrd for Packet<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (self, other) {
-            (Self::Int(left), Self::Int(right)) => left.partial_cmp(right),
-            (Self::List(left), Self::List(right)) => {
-                for (l, r) in zip(left, right) {
-                    if l < r {
-                        return Some(Ordering::Less);
-                    } else if l > r {
-                        return Some(Ordering::Greater);
-                    }
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> PartialEq for Packet<T> {
+    /// Packets are equal when neither orders before the other, so `5`, `[5]` and `[[5]]`
+    /// are all equal. Use [`Packet::identical`] to also compare their structure.
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Packet<T> {}
+
+impl<T: Ord> Packet<T> {
+    /// Whether two packets have exactly the same structure, not just the same ordering
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::{packet, Packet};
+    ///
+    /// let int: Packet = Packet::Int(5);
+    /// let list = packet![5];
+    ///
+    /// assert_eq!(int, list);
+    /// assert!(!int.identical(&list));
+    /// assert!(list.identical(&list.clone()));
+    /// ```
+    pub fn identical(&self, other: &Self) -> bool {
+        let mut stack = vec![(
+            std::slice::from_ref(self).iter(),
+            std::slice::from_ref(other).iter(),
+        )];
+
+        while let Some((left, right)) = stack.last_mut() {
+            match (left.next(), right.next()) {
+                (None, None) => {
+                    stack.pop();
                 }
+                (Some(Self::Int(l)), Some(Self::Int(r))) if l == r => (),
+                (Some(Self::List(l)), Some(Self::List(r))) if l.len() == r.len() => {
+                    stack.push((l.iter(), r.iter()))
+                }
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
 
-                Some(left.len().cmp(&right.len()))
+impl<T: Clone> Clone for Packet<T> {
+    fn clone(&self) -> Self {
+        let Self::List(items) = self else {
+            return match self {
+                Self::Int(n) => Self::Int(n.clone()),
+                Self::List(_) => unreachable!(),
+            };
+        };
+
+        // Each frame holds the source items still to copy and the copies made so far
+        let mut stack = vec![(items.iter(), Vec::with_capacity(items.len()))];
+
+        loop {
+            let (source, copied) = stack.last_mut().unwrap();
+
+            match source.next() {
+                Some(Self::Int(n)) => copied.push(Self::Int(n.clone())),
+                Some(Self::List(v)) => stack.push((v.iter(), Vec::with_capacity(v.len()))),
+                None => {
+                    let (_, done) = stack.pop().unwrap();
+                    match stack.last_mut() {
+                        Some((_, copied)) => copied.push(Self::List(done)),
+                        None => return Self::List(done),
+                    }
+                }
             }
-            (Self::Int(left), Self::List(right)) => {
-                Packet::List(vec![Packet::Int(*left)]).partial_cmp(&Packet::List((*right).clone()))
+        }
+    }
+}
+
+impl<T> Packet<T> {
+    /// Takes the items of a list, or returns the integer of an integer packet
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::{packet, Packet};
+    ///
+    /// let items = packet![1, [2]].into_items().unwrap();
+    /// assert!(items[1].identical(&packet![2]));
+    /// assert_eq!(Packet::Int(5).into_items(), Err(5));
+    /// ```
+    pub fn into_items(self) -> Result<Vec<Packet<T>>, T> {
+        let mut packet = std::mem::ManuallyDrop::new(self);
+
+        match &mut *packet {
+            Self::List(items) => Ok(std::mem::take(items)),
+            // SAFETY: the packet is never used or dropped again, so the integer is
+            // moved out exactly once
+            Self::Int(n) => Err(unsafe { std::ptr::read(n) }),
+        }
+    }
+}
+
+impl<T> Drop for Packet<T> {
+    fn drop(&mut self) {
+        let Self::List(items) = self else { return };
+
+        if items
+            .iter()
+            .all(|p| matches!(p, Self::Int(_)) || matches!(p, Self::List(v) if v.is_empty()))
+        {
+            return;
+        }
+
+        // Move every descendant into one flat vector, so each is dropped with no children
+        let mut pending = std::mem::take(items);
+        while let Some(mut packet) = pending.pop() {
+            if let Self::List(children) = &mut packet {
+                pending.append(children);
             }
-            (Self::List(left), Self::Int(right)) => {
-                Packet::List((*left).clone()).partial_cmp(&Packet::List(vec![Packet::Int(*right)]))
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Packet<T> {
+    /// Formats like the derived implementation, in both the compact and `{:#?}` forms
+    /// # Examples
+    /// Neither form, nor cloning, is limited by how deeply the packet is nested
+    /// ```
+    /// use std::fmt::{self, Write};
+    /// use advent_of_code_2022_13::Packet;
+    ///
+    /// let mut deep: Packet = Packet::Int(1);
+    /// for _ in 0..9_000 {
+    ///     deep = Packet::List(vec![deep]);
+    /// }
+    /// assert!(deep.clone().identical(&deep));
+    ///
+    /// let compact = format!("{deep:?}");
+    /// assert_eq!(compact.matches("List([").count(), 9_000);
+    /// assert!(compact.contains("List([Int(1)])"));
+    ///
+    /// // Counts the pretty form, which indents the innermost lines by 72,000 spaces
+    /// struct Len(usize);
+    /// impl Write for Len {
+    ///     fn write_str(&mut self, s: &str) -> fmt::Result {
+    ///         self.0 += s.len();
+    ///         Ok(())
+    ///     }
+    /// }
+    /// let mut len = Len(0);
+    /// write!(len, "{deep:#?}").unwrap();
+    /// assert!(len.0 > 72_000 * 9_000);
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pretty = f.alternate();
+
+        let Some(items) = debug_open(f, self, 0)? else {
+            return Ok(());
+        };
+        let mut stack = vec![(items, true)];
+
+        while let Some((items, first)) = stack.last_mut() {
+            match items.next() {
+                Some(packet) => {
+                    if !pretty && !*first {
+                        f.write_str(", ")?;
+                    }
+                    *first = false;
+
+                    let level = stack.len() * 2;
+                    debug_indent(f, level)?;
+                    match debug_open(f, packet, level)? {
+                        Some(items) => stack.push((items, true)),
+                        None if pretty => f.write_str(",\n")?,
+                        None => (),
+                    }
+                }
+                None => {
+                    stack.pop();
+                    let level = stack.len() * 2;
+                    if pretty {
+                        debug_indent(f, level + 1)?;
+                        f.write_str("],\n")?;
+                        debug_indent(f, level)?;
+                        f.write_str(if stack.is_empty() { ")" } else { "),\n" })?;
+                    } else {
+                        f.write_str("])")?;
+                    }
+                }
             }
         }
+
+        Ok(())
+    }
+}
+
+/// Indents to `level` when pretty-printing
+fn debug_indent(f: &mut fmt::Formatter, level: usize) -> fmt::Result {
+    if f.alternate() {
+        write_spaces(f, level * 4)
+    } else {
+        Ok(())
     }
 }
 
-impl FromStr for Packet {
-    type Err = std::io::Error;
+/// Writes `n` spaces a chunk at a time, as a formatting width panics past `u16::MAX`
+fn write_spaces(f: &mut impl fmt::Write, n: usize) -> fmt::Result {
+    const SPACES: &str = "                                                                ";
+
+    let mut left = n;
+    while left > 0 {
+        let chunk = left.min(SPACES.len());
+        f.write_str(&SPACES[..chunk])?;
+        left -= chunk;
+    }
+    Ok(())
+}
+
+/// Writes the head of a packet whose own line is indented to `level`, and
+/// returns the items of a non-empty list, which still need to be written
+fn debug_open<'a, T: fmt::Debug>(
+    f: &mut fmt::Formatter,
+    packet: &'a Packet<T>,
+    level: usize,
+) -> Result<Option<std::slice::Iter<'a, Packet<T>>>, fmt::Error> {
+    let pretty = f.alternate();
+
+    match packet {
+        Packet::Int(n) if pretty => {
+            f.write_str("Int(\n")?;
+            debug_indent(f, level + 1)?;
+            writeln!(f, "{n:?},")?;
+            debug_indent(f, level)?;
+            f.write_str(")")?;
+        }
+        Packet::Int(n) => write!(f, "Int({n:?})")?,
+        Packet::List(v) if v.is_empty() && pretty => {
+            f.write_str("List(\n")?;
+            debug_indent(f, level + 1)?;
+            f.write_str("[],\n")?;
+            debug_indent(f, level)?;
+            f.write_str(")")?;
+        }
+        Packet::List(v) if v.is_empty() => f.write_str("List([])")?,
+        Packet::List(v) if pretty => {
+            f.write_str("List(\n")?;
+            debug_indent(f, level + 1)?;
+            f.write_str("[\n")?;
+            return Ok(Some(v.iter()));
+        }
+        Packet::List(v) => {
+            f.write_str("List([")?;
+            return Ok(Some(v.iter()));
+        }
+    }
+
+    Ok(None)
+}
 
+impl<T: PacketInt> Packet<T> {
+    /// Parses a packet directly from bytes, without requiring them to be valid UTF-8
     /// # Examples
     /// ```
     /// use advent_of_code_2022_13::Packet;
     ///
-    /// assert_eq!(
-    ///     "[1,1,3,1,1]".parse::<Packet>().unwrap(),
-    ///     Packet::List([1,1,3,1,1].iter().map(|x| Packet::Int(*x)).collect())
-    /// );
-    /// assert_eq!(
-    ///     "[9]".parse::<Packet>().unwrap(),
-    ///     Packet::List(vec![Packet::Int(9)])
-    /// );
-    /// assert_eq!(
-    ///     "[]".parse::<Packet>().unwrap(),
-    ///     Packet::List(vec![])
-    /// );
-    /// assert_eq!(
-    ///     "[[8,7,6]]".parse::<Packet>().unwrap(),
-    ///     Packet::List(vec![Packet::List([8,7,6].iter().map(|x| Packet::Int(*x)).collect())])
-    /// );
-    /// assert_eq!(
-    ///     "[[1],[2,3,4]]".parse::<Packet>().unwrap(),
-    ///     Packet::List(vec![Packet::List(vec![Packet::Int(1)]),Packet::List([2,3,4].iter().map(|x| Packet::Int(*x)).collect())])
-    /// );
-    /// assert_eq!(
-    ///     "[[4,4],4,4]".parse::<Packet>().unwrap(),
-    ///     Packet::List(vec![Packet::List(vec![Packet::Int(4), Packet::Int(4)]), Packet::Int(4), Packet::Int(4)])
-    /// );
+    /// let packet: Packet = Packet::from_bytes(b"[1,[2]]").unwrap();
+    /// assert_eq!(packet, "[1,[2]]".parse().unwrap());
+    /// assert!(Packet::<u8>::from_bytes(b"[1,\xff]").is_err());
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PacketParseError> {
+        parse::parse(bytes)
+    }
+}
+
+impl<T: PacketInt> FromStr for Packet<T> {
+    type Err = PacketParseError;
+
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::{packet, Packet};
+    ///
+    /// assert!("[1,1,3,1,1]".parse::<Packet>().unwrap().identical(&packet![1, 1, 3, 1, 1]));
+    /// assert!("[9]".parse::<Packet>().unwrap().identical(&packet![9]));
+    /// assert!(!"[[9]]".parse::<Packet>().unwrap().identical(&packet![9]));
+    /// assert!("[]".parse::<Packet>().unwrap().identical(&packet![]));
+    /// assert!("[[8,7,6]]".parse::<Packet>().unwrap().identical(&packet![[8, 7, 6]]));
+    /// assert!("[[1],[2,3,4]]".parse::<Packet>().unwrap().identical(&packet![[1], [2, 3, 4]]));
+    /// assert!("[[4,4],4,4]".parse::<Packet>().unwrap().identical(&packet![[4, 4], 4, 4]));
+    /// ```
+    ///
+    /// Malformed input is reported with its location rather than panicking
+    /// ```
+    /// use advent_of_code_2022_13::{Packet, PacketParseError, JsonType};
+    ///
+    /// let err = "[1,[2,3]".parse::<Packet>().unwrap_err();
+    /// assert!(matches!(err, PacketParseError::UnbalancedBracket { .. }));
+    /// assert_eq!(err.position().offset, 0);
+    ///
+    /// let err = "[1,\n[256]]".parse::<Packet>().unwrap_err();
+    /// assert!(matches!(err, PacketParseError::IntegerOverflow { .. }));
+    /// assert_eq!((err.position().line, err.position().column), (2, 2));
+    /// assert!("[1,\n[256]]".parse::<Packet<u16>>().is_ok());
+    /// assert!(matches!("[-1]".parse::<Packet>(), Err(PacketParseError::IntegerOverflow { .. })));
+    /// assert!("[-1]".parse::<Packet<i64>>().is_ok());
+    ///
+    /// assert!(matches!(
+    ///     "[1,\"a\"]".parse::<Packet>(),
+    ///     Err(PacketParseError::UnsupportedJsonType { found: JsonType::String, .. })
+    /// ));
+    /// assert!(matches!("[1] 2".parse::<Packet>(), Err(PacketParseError::TrailingInput { .. })));
+    /// assert!(matches!("[1,]".parse::<Packet>(), Err(PacketParseError::UnexpectedToken { .. })));
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::from_json_val(&serde_json::from_str::<Value>(s).expect("Should parse as json"))
+        Self::from_bytes(s.as_bytes())
     }
 }
 
-impl Packet {
-    fn from_json_val(val: &Value) -> Result<Self, std::io::Error> {
-        match val {
-            Value::Number(n) => Ok(Packet::Int(n.as_i64().unwrap() as u8)),
-            Value::Array(v) => Ok(Packet::List(
-                v.iter().map(|x| Self::from_json_val(x).unwrap()).collect(),
-            )),
-            _ => panic!("This should never happen!"),
-        }
+/// An integer packet
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{FlatPacket, Packet};
+///
+/// let packet: Packet = Packet::from(5);
+/// assert!(packet.identical(&Packet::Int(5)));
+///
+/// let flat: FlatPacket = "[1,[2]]".parse().unwrap();
+/// let packet = Packet::from(&flat);
+/// assert_eq!(packet.to_string(), "[1,[2]]");
+/// ```
+impl<T: PacketInt> From<T> for Packet<T> {
+    fn from(n: T) -> Self {
+        Packet::Int(n)
+    }
+}
+
+/// A list packet holding the given items
+impl<T> From<Vec<Packet<T>>> for Packet<T> {
+    fn from(items: Vec<Packet<T>>) -> Self {
+        Packet::List(items)
+    }
+}
+
+/// A list packet holding the collected items
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{packet, Packet};
+///
+/// let packet: Packet = (1..=3).map(Packet::from).collect();
+/// assert!(packet.identical(&packet![1, 2, 3]));
+/// ```
+impl<T> FromIterator<Packet<T>> for Packet<T> {
+    fn from_iter<I: IntoIterator<Item = Packet<T>>>(items: I) -> Self {
+        Packet::List(items.into_iter().collect())
+    }
+}
+
+/// A list packet holding the collected integers
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{packet, Packet};
+///
+/// let packet: Packet = [1, 1, 3, 1, 1].into_iter().collect();
+/// assert!(packet.identical(&packet![1, 1, 3, 1, 1]));
+/// ```
+impl<T> FromIterator<T> for Packet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(ints: I) -> Self {
+        Packet::List(ints.into_iter().map(Packet::Int).collect())
     }
 }
 
 /// Finds the pairs of packets in the correct order and returns the sum of their indices
+///
+/// Like the other functions that take a whole input, this accepts a `str`, a byte
+/// slice or anything else that is [`AsRef<[u8]>`](AsRef), so input need not be valid
+/// UTF-8 outside the packets themselves.
 /// # Examples
 /// ```
 /// use advent_of_code_2022_13::sum_correct;
 ///
 /// assert_eq!(
-///     13,
+///     Ok(13),
 ///     sum_correct(concat!(
 ///     "[1,1,3,1,1]\n",
 ///     "[1,1,5,1,1]\n",
@@ -149,12 +691,214 @@ impl Packet {
 ///     "[1,[2,[3,[4,[5,6,0]]]],8,9]"
 /// )));
 /// ```
-pub fn sum_correct(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .map(Pair::new)
+///
+/// The first malformed pair is reported rather than panicking
+/// ```
+/// use advent_of_code_2022_13::{sum_correct, PairErrorKind};
+///
+/// let err = sum_correct("[1]\n[2]\n\n[3]\n[4]\n\n[5]\n").unwrap_err();
+/// assert_eq!((err.block, err.line, err.kind), (3, 7, PairErrorKind::MissingRight));
+/// ```
+pub fn sum_correct(input: &(impl AsRef<[u8]> + ?Sized)) -> Result<usize, PairParseError> {
+    sum_correct_as::<u8>(input)
+}
+
+/// Like [`sum_correct`], but reading integers into `T` rather than `u8`
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{sum_correct, sum_correct_as};
+///
+/// let input = "[1000]\n[999]\n\n[-1]\n[1]";
+/// assert!(sum_correct(input).is_err());
+/// assert_eq!(sum_correct_as::<i32>(input), Ok(2));
+/// ```
+pub fn sum_correct_as<T: PacketInt>(
+    input: &(impl AsRef<[u8]> + ?Sized),
+) -> Result<usize, PairParseError> {
+    let mut sum = 0;
+
+    for (i, pair) in parse_pairs::<T>(input).enumerate() {
+        if pair?.is_in_order() {
+            sum += i + 1;
+        }
+    }
+
+    Ok(sum)
+}
+
+/// Parses each blank-line-separated block of the input as a pair, carrying on past
+/// malformed blocks
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{parse_pairs, Pair};
+///
+/// let pairs: Vec<_> = parse_pairs::<u8>("[1]\n[2]\n\n[3]\n\n[5]\n[4]").collect();
+/// assert!(pairs[0].as_ref().unwrap().is_in_order());
+/// assert_eq!(pairs[1].as_ref().unwrap_err().line, 4);
+/// assert!(!pairs[2].as_ref().unwrap().is_in_order());
+/// ```
+///
+/// Blocks are separated just as by [`PairReader`], so any number of blank lines,
+/// whitespace on them and CRLF line endings are all accepted
+/// ```
+/// use advent_of_code_2022_13::{parse_pairs, sum_correct, PairReader};
+///
+/// let input = "\r\n[1]\r\n[2]\r\n \r\n\n\t\n[4]\r\n[3]\r\n\r\n";
+/// let pairs: Vec<_> = parse_pairs::<u8>(input).map(Result::unwrap).collect();
+/// let read: Vec<_> = PairReader::new(input.as_bytes()).map(|r| r.unwrap().1).collect();
+/// assert_eq!(pairs, read);
+/// assert_eq!(sum_correct(input), Ok(1));
+///
+/// let err = sum_correct("[1]\n[2]\n\n\n[3]\n").unwrap_err();
+/// assert_eq!((err.block, err.line), (2, 5));
+/// ```
+pub fn parse_pairs<T: PacketInt>(
+    input: &(impl AsRef<[u8]> + ?Sized),
+) -> impl Iterator<Item = Result<Pair<T>, PairParseError>> + '_ {
+    blocks(input.as_ref())
+        .enumerate()
+        .map(|block| parse_block(block))
+}
+
+/// A blank-line-separated block of input, with the number of lines and bytes before it
+type Block<'a> = (usize, usize, &'a [u8]);
+
+/// Splits the input into the blocks that should each hold a pair, separated as by
+/// [`PairReader`]: by any number of lines holding nothing but whitespace
+fn blocks(input: &[u8]) -> impl Iterator<Item = Block<'_>> {
+    let blank = |line: &[u8]| line.iter().all(u8::is_ascii_whitespace);
+    let (mut lines, mut bytes) = (0, 0);
+    let mut rest = input.split_inclusive(|&b| b == b'\n').peekable();
+
+    std::iter::from_fn(move || {
+        while let Some(line) = rest.next_if(|line| blank(line)) {
+            lines += 1;
+            bytes += line.len();
+        }
+
+        let start = (lines, bytes);
+        while let Some(line) = rest.next_if(|line| !blank(line)) {
+            lines += 1;
+            bytes += line.len();
+        }
+
+        (bytes > start.1).then(|| (start.0, start.1, &input[start.1..bytes]))
+    })
+}
+
+/// Parses the block at 0-based index `i`, reporting errors against the whole input
+fn parse_block<T: PacketInt>(
+    (i, (lines, bytes, block)): (usize, Block<'_>),
+) -> Result<Pair<T>, PairParseError> {
+    Pair::from_bytes(block).map_err(|e| e.in_block(i + 1, lines, bytes))
+}
+
+/// Parses every line of the input as a packet, skipping blank lines
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{parse_packets, Packet};
+///
+/// let packets: Vec<Packet> = parse_packets("[1]\n[2]\n\n[3]\n").collect::<Result<_, _>>().unwrap();
+/// assert_eq!(packets.len(), 3);
+///
+/// let err = parse_packets::<u8>("[1]\n\n[2,\n").nth(1).unwrap().unwrap_err();
+/// assert_eq!(err.position().line, 3);
+/// ```
+pub fn parse_packets<T: PacketInt>(
+    input: &(impl AsRef<[u8]> + ?Sized),
+) -> impl Iterator<Item = Result<Packet<T>, PacketParseError>> + '_ {
+    parse_lines(input).flatten()
+}
+
+/// Parses every line of the input as a packet, yielding `None` for each blank line, so
+/// that the index of each item is its 0-based line number
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{packet, parse_lines};
+///
+/// let lines: Vec<_> = parse_lines::<u8>("[1]\n \n[2,\n").collect();
+/// assert_eq!(lines.len(), 3);
+/// assert!(lines[0].as_ref().unwrap().as_ref().unwrap().identical(&packet![1]));
+/// assert!(lines[1].is_none());
+/// assert_eq!(lines[2].as_ref().unwrap().as_ref().unwrap_err().position().line, 3);
+/// ```
+pub fn parse_lines<T: PacketInt>(
+    input: &(impl AsRef<[u8]> + ?Sized),
+) -> impl Iterator<Item = Option<Result<Packet<T>, PacketParseError>>> + '_ {
+    let input = input.as_ref();
+
+    lines(input).enumerate().map(move |(i, line)| {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+
+        let bytes = line.as_ptr() as usize - input.as_ptr() as usize;
+        Some(Packet::from_bytes(line).map_err(|e| e.shifted(i, bytes)))
+    })
+}
+
+/// The divider packets `[[2]]` and `[[6]]` used by [`decoder_key`] in the puzzle. For
+/// other integer types, parse the dividers instead.
+pub fn default_dividers() -> Vec<Packet> {
+    vec![packet![[2]], packet![[6]]]
+}
+
+/// Finds where each divider packet would land if all the packets in the input, plus the
+/// dividers, were sorted, and returns the product of those 1-based positions.
+///
+/// Blank lines are ignored. Positions are found by counting the packets that order before
+/// each divider, so this takes O(n) comparisons per divider rather than a full sort.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{decoder_key, default_dividers, Packet};
+///
+/// let input = concat!(
+///     "[1,1,3,1,1]\n[1,1,5,1,1]\n\n",
+///     "[[1],[2,3,4]]\n[[1],4]\n\n",
+///     "[9]\n[[8,7,6]]\n\n",
+///     "[[4,4],4,4]\n[[4,4],4,4,4]\n\n",
+///     "[7,7,7,7]\n[7,7,7]\n\n",
+///     "[]\n[3]\n\n",
+///     "[[[]]]\n[[]]\n\n",
+///     "[1,[2,[3,[4,[5,6,7]]]],8,9]\n[1,[2,[3,[4,[5,6,0]]]],8,9]\n",
+/// );
+///
+/// assert_eq!(decoder_key(input, &default_dividers()), Ok(140));
+/// assert_eq!(decoder_key(input, &["[[3]]".parse::<Packet>().unwrap()]), Ok(10));
+///
+/// let err = decoder_key("[1]\n\n[2,\n", &default_dividers()).unwrap_err();
+/// assert_eq!(err.position().line, 3);
+///
+/// let dividers = ["[[2]]", "[[6]]"].map(|s| s.parse::<Packet<u64>>().unwrap());
+/// assert_eq!(decoder_key("[1000]\n[1]", &dividers), Ok(6));
+/// ```
+pub fn decoder_key<T: PacketInt>(
+    input: &(impl AsRef<[u8]> + ?Sized),
+    dividers: &[Packet<T>],
+) -> Result<usize, PacketParseError> {
+    let mut below = vec![0; dividers.len()];
+
+    for packet in parse_packets::<T>(input) {
+        let packet = packet?;
+        for (count, divider) in below.iter_mut().zip(dividers) {
+            if packet < *divider {
+                *count += 1;
+            }
+        }
+    }
+
+    Ok(dividers
+        .iter()
         .enumerate()
-        .filter(|(_, p)| p.is_in_order())
-        .map(|(i, _)| i + 1)
-        .sum()
+        .map(|(i, divider)| {
+            // Ties between dividers go to whichever was given first
+            let dividers_below = dividers
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| other < divider || (other == divider && j < i))
+                .count();
+
+            below[i] + dividers_below + 1
+        })
+        .product())
 }
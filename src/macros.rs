@@ -0,0 +1,63 @@
+/// Builds a [`Packet`](crate::Packet) list from a literal written like the puzzle input
+///
+/// Items are integer expressions or bracketed lists of items. The integer type is
+/// inferred as usual, so give the packet a type when nothing else fixes it.
+///
+/// A list whose items are each a single token, such as a literal, a name, a bracketed
+/// list or a parenthesised expression, is expanded in one step however long it is.
+/// Items before the last of any longer ones, like `-1` or `n * 2`, are split off one at
+/// a time, so a list with over a hundred of those needs a higher `recursion_limit`
+/// unless they are parenthesised.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{packet, Packet};
+///
+/// let packet: Packet = packet![1, [2, 3], []];
+/// assert!(packet.identical(&"[1,[2,3],[]]".parse().unwrap()));
+///
+/// let n = 4;
+/// let packet: Packet<i32> = packet![[[-n]], n * 2,];
+/// assert_eq!(packet.to_string(), "[[[-4]],8]");
+/// assert!(packet![].identical(&Packet::<u8>::List(vec![])));
+///
+/// let long: Packet = packet![
+///     0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7,
+///     8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5,
+///     6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3,
+///     4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1,
+///     2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, [0], [1], [2], [3], [4], [5],
+/// ];
+/// let Packet::List(items) = &long else { unreachable!() };
+/// assert_eq!(items.len(), 136);
+///
+/// let signed: Packet<i8> = packet![(-1), [-2, -3], -4, -5];
+/// assert_eq!(signed.to_string(), "[-1,[-2,-3],-4,-5]");
+/// ```
+#[macro_export]
+macro_rules! packet {
+    (@item [$($list:tt)*]) => {
+        $crate::packet![$($list)*]
+    };
+    (@item $int:expr) => {
+        $crate::Packet::Int($int)
+    };
+
+    // Once the remaining items are single tokens they are all expanded together
+    (@list [$($done:expr,)*] $($item:tt),* $(,)?) => {
+        ::std::vec![$($done,)* $($crate::packet!(@item $item)),*]
+    };
+    // Otherwise items are munched one at a time onto the expressions already built
+    (@list [$($done:expr,)*] [$($list:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::packet!(@list [$($done,)* $crate::packet![$($list)*],] $($($rest)*)?)
+    };
+    (@list [$($done:expr,)*] $int:expr $(, $($rest:tt)*)?) => {
+        $crate::packet!(@list [$($done,)* $crate::Packet::Int($int),] $($($rest)*)?)
+    };
+
+    ($($item:tt),* $(,)?) => {
+        $crate::Packet::List(::std::vec![$($crate::packet!(@item $item)),*])
+    };
+    ($($items:tt)*) => {
+        $crate::Packet::List($crate::packet!(@list [] $($items)*))
+    };
+}
@@ -1,11 +1,755 @@
+#[cfg(feature = "parallel")]
+use advent_of_code_2022_13::parse_pairs_par;
+use advent_of_code_2022_13::{
+    decoder_key, default_dividers, parse_lines, parse_pairs, shrink, Comparison, ExternalSort,
+    ListLength, Packet, PacketGenerator, Pair, PairParseError, PairReader, Query, ReadError,
+    SortError,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::env;
+use std::fmt;
 use std::fs;
-use advent_of_code_2022_13::sum_correct;
+use std::io::{self, BufRead, Read, Write};
+use std::process::{Command, ExitCode, Stdio};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-fn main() {
-    let args = env::args().collect::<Vec<_>>();
-    let file_path = &args[1];
-    let contents = fs::read_to_string(file_path).expect("Should have been able to read {file_path}");
+const USAGE: &str = "\
+Usage: advent-of-code-2022-13 <COMMAND> [OPTIONS] [FILE]
 
-    println!("The sum of the indices of packets in correct order is: {}", sum_correct(&contents));
+Commands:
+  part1 [OPTIONS] [FILE]    Sum the indices of the pairs that are in order
+      --format <FORMAT>     Report each pair and then the sum as `text`, `json` or
+                            `csv`, whose last row is `sum` [default: text]
+      --jobs <N>            Parse and compare pairs on N threads; needs the
+                            `parallel` feature for more than 1 [default: 1]
+  part2 [FILE]              Multiply the sorted positions of the divider packets
+  sort [OPTIONS] [FILE]     Print every packet in order, one per line, spilling to
+                            temporary files if they do not fit in memory
+      --unique              Print only the first of each run of equal packets
+      --reverse             Print the greatest packets first
+      --memory <SIZE>       Memory to sort in, in bytes or with a K, M or G
+                            suffix [default: 256M]
+      --temp-dir <DIR>      Directory for temporary files [default: the system's]
+  compare <LEFT> <RIGHT>    Explain how two packets compare; exits 1 if not in order
+  query [OPTIONS] <QUERY> [FILE]
+                            Print the line, path and value of every match of
+                            QUERY, e.g. `..[?int > 5]`, in each packet; exits 1
+                            if nothing matches
+      --packets             Print each packet with a match, rather than the matches
+  validate [FILE]           Report every malformed pair, reading one pair at a time
+  fmt [OPTIONS] [FILE]      Print every packet in canonical form, keeping blank lines
+      --pretty              Spread packets over several lines
+      --indent <N>          Spaces per level of nesting with --pretty [default: 2]
+      --width <N>           Line width to fit lists within with --pretty [default: 80]
+  generate [OPTIONS] <PAIRS>
+                            Print puzzle input of PAIRS random pairs
+      --seed <N>            Seed to generate from, printed to standard error if
+                            not given [default: random]
+      --max-depth <N>       Deepest nesting of lists [default: 4]
+      --min-len <N>         Fewest items in a list that is not empty [default: 1]
+      --max-len <N>         Most items in a list that is not empty [default: 5]
+      --mean-len <X>        Draw list lengths averaging X, without a maximum,
+                            instead of between --min-len and --max-len
+      --min-int <N>         Smallest integer [default: 0]
+      --max-int <N>         Largest integer [default: 10]
+      --empty <P>           Probability that a list is empty [default: 0.1]
+      --lists <P>           Probability that an item is a list [default: 0.3]
+  shrink [OPTIONS] [FILE] -- <ORACLE>...
+                            Shrink a pair that the ORACLE command disagrees with
+                            to a minimal one. ORACLE reads the pair on standard
+                            input and exits 0 if it is in order or 1 if not.
+      --status <N>          Keep pairs on which ORACLE exits with N instead
+  help                      Print this message
+
+FILE defaults to `-`, which reads standard input. part1, part2 and fmt also take:
+      --mmap                Map FILE into memory rather than reading it onto the
+                            heap; needs the `mmap` feature. FILE must not change
+                            while it is mapped.
+
+Exit status:
+  0   Success
+  1   `compare` found the packets out of order, or `query` matched nothing
+  64  Invalid command line
+  65  Malformed input
+  74  Input could not be read or output could not be written
+";
+
+/// The ways a command can fail, each with its own exit status
+enum Error {
+    Usage(String),
+    Invalid(String),
+    Io(String, io::Error),
+}
+
+impl Error {
+    fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Self::Usage(_) => 64,
+            Self::Invalid(_) => 65,
+            Self::Io(..) => 74,
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(e) => write!(f, "{e}\n\n{USAGE}"),
+            Self::Invalid(e) => write!(f, "{e}"),
+            Self::Io(path, e) => write!(f, "{path}: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io("<stdout>".to_string(), e)
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(code) => code,
+        // A closed pipe, e.g. from `| head`, is not worth reporting
+        Err(Error::Io(_, e)) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            e.exit_code()
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<ExitCode, Error> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(Error::Usage("missing command".to_string()));
+    };
+
+    // Anything after `--` belongs to another command
+    if rest
+        .iter()
+        .take_while(|&arg| arg != "--")
+        .any(|arg| arg == "-h" || arg == "--help")
+    {
+        print!("{USAGE}");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut out = io::stdout().lock();
+
+    match command.as_str() {
+        "-h" | "--help" | "help" => print!("{USAGE}"),
+        "part1" => {
+            let args = Args::parse(rest, &["--mmap"], &["--format", "--jobs"], 1)?;
+            let format = Format::parse(args.value("--format").unwrap_or("text"))?;
+            let jobs = args.number("--jobs", 1)?;
+            let (path, input) = read_input(&args)?;
+            let workers = Workers::new(jobs)?;
+            let pairs = workers
+                .parse(input.as_ref())
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| invalid(&path, e))?;
+
+            report(&mut out, format, &pairs, &workers.explain(&pairs))?;
+        }
+        "part2" => {
+            let (path, input) = read_input(&Args::parse(rest, &["--mmap"], &[], 1)?)?;
+            let key = decoder_key(&input, &default_dividers()).map_err(|e| invalid(&path, e))?;
+            writeln!(out, "The decoder key for the distress signal is: {key}")?;
+        }
+        "sort" => {
+            let args = Args::parse(
+                rest,
+                &["--unique", "--reverse"],
+                &["--memory", "--temp-dir"],
+                1,
+            )?;
+            let mut sort = ExternalSort::new()
+                .unique(args.flag("--unique"))
+                .reverse(args.flag("--reverse"))
+                .memory(args.size("--memory", 256 << 20)?);
+            if let Some(dir) = args.value("--temp-dir") {
+                sort = sort.temp_dir(dir);
+            }
+
+            let (path, input) = open_input(&args)?;
+            sort.sort(input, &mut out).map_err(|e| match e {
+                SortError::Parse(e) => invalid(&path, e),
+                SortError::Read(e) => Error::Io(path, e),
+                SortError::Write(e) => e.into(),
+                SortError::Temp(e) => Error::Io("temporary file".to_string(), e),
+            })?;
+        }
+        "compare" => {
+            let args = Args::parse(rest, &[], &[], 2)?;
+            let [left, right] = args.positional.as_slice() else {
+                return Err(Error::Usage("compare needs two packets".to_string()));
+            };
+            let left: Packet = left.parse().map_err(|e| invalid("left", e))?;
+            let right: Packet = right.parse().map_err(|e| invalid("right", e))?;
+
+            let comparison = left.explain_cmp(&right);
+            writeln!(out, "{comparison}")?;
+            if comparison.ordering.is_gt() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        "query" => {
+            let mut args = Args::parse(rest, &["--packets"], &[], 2)?;
+            if args.positional.is_empty() {
+                return Err(Error::Usage("query needs a query".to_string()));
+            }
+            let query: Query = args
+                .positional
+                .remove(0)
+                .parse()
+                .map_err(|e| Error::Usage(format!("invalid query: {e}")))?;
+
+            let (path, mut input) = open_input(&args)?;
+            let mut out = io::BufWriter::new(&mut out);
+            let (mut line, mut lines, mut matched) = (Vec::new(), 0, false);
+
+            loop {
+                line.clear();
+                if input
+                    .read_until(b'\n', &mut line)
+                    .map_err(|e| Error::Io(path.clone(), e))?
+                    == 0
+                {
+                    break;
+                }
+                lines += 1;
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+
+                let packet: Packet = Packet::from_bytes(&line)
+                    .map_err(|e| invalid(&format!("{path}: line {lines}"), e))?;
+                let matches = packet.select(&query);
+                matched |= !matches.is_empty();
+
+                if args.flag("--packets") {
+                    if !matches.is_empty() {
+                        writeln!(out, "{lines}:{packet}")?;
+                    }
+                    continue;
+                }
+                for m in matches {
+                    writeln!(out, "{lines}:${} {}", m.path_string(), m.value)?;
+                }
+            }
+
+            out.flush()?;
+            if !matched {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        "validate" => {
+            let (path, input) = open_input(&Args::parse(rest, &[], &[], 1)?)?;
+            let (mut valid, mut invalid) = (0, 0);
+
+            for pair in PairReader::new(input) {
+                match pair {
+                    Ok(_) => valid += 1,
+                    Err(ReadError::Parse(e)) => {
+                        eprintln!("{path}: {e}");
+                        invalid += 1;
+                    }
+                    Err(ReadError::Io(e)) => return Err(Error::Io(path, e)),
+                }
+            }
+
+            if invalid > 0 {
+                return Err(Error::Invalid(format!(
+                    "{path}: {invalid} of {} pairs are malformed",
+                    valid + invalid
+                )));
+            }
+            writeln!(out, "{path}: {valid} pairs are valid")?;
+        }
+        "fmt" => {
+            let args = Args::parse(rest, &["--pretty", "--mmap"], &["--indent", "--width"], 1)?;
+            let (path, input) = read_input(&args)?;
+            let indent = args.number("--indent", 2)?;
+            let width = args.number("--width", 80)?;
+
+            for line in parse_lines(input.as_ref()) {
+                let Some(packet) = line else {
+                    writeln!(out)?;
+                    continue;
+                };
+
+                let packet: Packet = packet.map_err(|e| invalid(&path, e))?;
+                if args.flag("--pretty") {
+                    writeln!(out, "{}", packet.pretty().indent(indent).width(width))?;
+                } else {
+                    writeln!(out, "{packet}")?;
+                }
+            }
+        }
+        "generate" => {
+            let args = Args::parse(
+                rest,
+                &[],
+                &[
+                    "--seed",
+                    "--max-depth",
+                    "--min-len",
+                    "--max-len",
+                    "--mean-len",
+                    "--min-int",
+                    "--max-int",
+                    "--empty",
+                    "--lists",
+                ],
+                1,
+            )?;
+            let Some(pairs) = args.positional.first() else {
+                return Err(Error::Usage("generate needs a number of pairs".to_string()));
+            };
+            let pairs: usize = pairs
+                .parse()
+                .map_err(|_| Error::Usage(format!("expected a number of pairs, not `{pairs}`")))?;
+
+            let list_len = match args.value("--mean-len") {
+                Some(_)
+                    if args.value("--min-len").is_some() || args.value("--max-len").is_some() =>
+                {
+                    return Err(Error::Usage(
+                        "option `--mean-len` cannot be used with `--min-len` or `--max-len`"
+                            .to_string(),
+                    ))
+                }
+                Some(_) => ListLength::Geometric {
+                    mean: args.number("--mean-len", 0.0)?,
+                },
+                None => ListLength::Uniform {
+                    min: args.number("--min-len", 1)?,
+                    max: args.number("--max-len", 5)?,
+                },
+            };
+
+            let max_depth = args.number("--max-depth", 4)?;
+            let ints = args.number("--min-int", 0)?..=args.number("--max-int", 10)?;
+            let empty = args.probability("--empty", 0.1)?;
+            let lists = args.probability("--lists", 0.3)?;
+
+            let seed = match args.value("--seed") {
+                Some(_) => args.number("--seed", 0)?,
+                None => {
+                    let seed = random_seed();
+                    eprintln!("seed: {seed}");
+                    seed
+                }
+            };
+
+            let mut generator = PacketGenerator::new(seed)
+                .max_depth(max_depth)
+                .list_len(list_len)
+                .ints(ints)
+                .empty_list_probability(empty)
+                .list_probability(lists);
+
+            let mut out = io::BufWriter::new(&mut out);
+            for i in 0..pairs {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                let pair: Pair<i64> = generator.pair();
+                writeln!(out, "{}\n{}", pair.left(), pair.right())?;
+            }
+            out.flush()?;
+        }
+        "shrink" => {
+            let Some(split) = rest.iter().position(|arg| arg == "--") else {
+                return Err(Error::Usage(
+                    "shrink needs an oracle command after `--`".to_string(),
+                ));
+            };
+            let (rest, oracle) = (&rest[..split], &rest[split + 1..]);
+            if oracle.is_empty() {
+                return Err(Error::Usage(
+                    "shrink needs an oracle command after `--`".to_string(),
+                ));
+            }
+
+            let args = Args::parse(rest, &[], &["--status"], 1)?;
+            let status = match args.value("--status") {
+                Some(_) => Some(args.number("--status", 0)?),
+                None => None,
+            };
+            let (path, input) = read_input(&args)?;
+            let pair = Pair::from_bytes(input.as_ref()).map_err(|e| invalid(&path, e))?;
+
+            // The exit status that makes a pair worth keeping
+            let wanted = |pair: &Pair| status.unwrap_or(if pair.is_in_order() { 1 } else { 0 });
+            let (mut tests, mut error) = (0, None);
+            let mut interesting = |pair: &Pair| {
+                if error.is_some() {
+                    return false;
+                }
+                tests += 1;
+                match run_oracle(oracle, pair) {
+                    Ok(code) => code == Some(wanted(pair)),
+                    Err(e) => {
+                        error = Some(e);
+                        false
+                    }
+                }
+            };
+
+            if !interesting(&pair) {
+                if let Some(e) = error {
+                    return Err(Error::Io(oracle[0].clone(), e));
+                }
+                return Err(Error::Invalid(format!(
+                    "{path}: `{}` does not exit with {} on the pair",
+                    oracle[0],
+                    wanted(&pair)
+                )));
+            }
+            let shrunk = shrink(pair, &mut interesting);
+            if let Some(e) = error {
+                return Err(Error::Io(oracle[0].clone(), e));
+            }
+
+            eprintln!("shrunk in {tests} tests");
+            writeln!(out, "{}\n{}", shrunk.left(), shrunk.right())?;
+        }
+        command => return Err(Error::Usage(format!("unknown command `{command}`"))),
+    }
+
+    out.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The layouts `part1` can report in
+#[derive(Clone, Copy)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Self, Error> {
+        match name {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(Error::Usage(format!(
+                "unknown format `{name}`, expected text, json or csv"
+            ))),
+        }
+    }
+}
+
+/// Writes the verdict on each pair, with the path that decided it, then the sum of
+/// the indices of the pairs in order
+fn report(
+    out: &mut impl Write,
+    format: Format,
+    pairs: &[Pair],
+    comparisons: &[Comparison],
+) -> io::Result<()> {
+    match format {
+        Format::Json => out.write_all(b"{\"pairs\":[")?,
+        Format::Csv => writeln!(out, "index,left,right,in_order,path")?,
+        Format::Text => (),
+    }
+
+    let mut sum = 0;
+    for (i, (pair, comparison)) in pairs.iter().zip(comparisons).enumerate() {
+        let index = i + 1;
+        let in_order = comparison.ordering.is_le();
+        if in_order {
+            sum += index;
+        }
+
+        let (left, right) = (pair.left(), pair.right());
+        match format {
+            Format::Text => {
+                let verdict = if in_order { "in order" } else { "not in order" };
+                writeln!(out, "{index}: {verdict}, {comparison}")?
+            }
+            Format::Json => {
+                let separator = if i == 0 { "" } else { "," };
+                let path = comparison.path.iter().map(usize::to_string);
+                write!(
+                    out,
+                    "{separator}{{\"index\":{index},\"left\":{left},\"right\":{right},\
+                     \"in_order\":{in_order},\"path\":[{}]}}",
+                    path.collect::<Vec<_>>().join(",")
+                )?
+            }
+            Format::Csv => {
+                let path = comparison.path_string();
+                writeln!(out, "{index},\"{left}\",\"{right}\",{in_order},{path}")?
+            }
+        }
+    }
+
+    match format {
+        Format::Text => writeln!(
+            out,
+            "The sum of the indices of packets in correct order is: {sum}"
+        ),
+        Format::Json => writeln!(out, "],\"sum\":{sum}}}"),
+        Format::Csv => writeln!(out, "sum,,,,{sum}"),
+    }
+}
+
+/// Where `part1` parses and compares pairs: on this thread, or on a pool of threads
+enum Workers {
+    Serial,
+    #[cfg(feature = "parallel")]
+    Pool(rayon::ThreadPool),
+}
+
+impl Workers {
+    /// Workers for `jobs` threads, with a pool only if there is more than one
+    fn new(jobs: usize) -> Result<Self, Error> {
+        match jobs {
+            0 => Err(Error::Usage(
+                "option `--jobs` needs at least 1 thread".to_string(),
+            )),
+            1 => Ok(Self::Serial),
+            #[cfg(feature = "parallel")]
+            jobs => rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()
+                .map(Self::Pool)
+                .map_err(|e| Error::Io("--jobs".to_string(), io::Error::other(e))),
+            #[cfg(not(feature = "parallel"))]
+            _ => Err(Error::Usage(
+                "option `--jobs` needs the `parallel` feature for more than 1 thread".to_string(),
+            )),
+        }
+    }
+
+    /// Parses every pair of the input, in input order
+    fn parse(&self, input: &[u8]) -> Vec<Result<Pair, PairParseError>> {
+        match self {
+            Self::Serial => parse_pairs(input).collect(),
+            #[cfg(feature = "parallel")]
+            Self::Pool(pool) => pool.install(|| parse_pairs_par(input)),
+        }
+    }
+
+    /// Explains how each pair compares, in input order
+    fn explain<'a>(&self, pairs: &'a [Pair]) -> Vec<Comparison<'a>> {
+        match self {
+            Self::Serial => pairs.iter().map(Pair::explain).collect(),
+            #[cfg(feature = "parallel")]
+            Self::Pool(pool) => pool.install(|| pairs.par_iter().map(Pair::explain).collect()),
+        }
+    }
+}
+
+/// A seed that differs from run to run, for when none is given
+fn random_seed() -> u64 {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+
+    time ^ u64::from(std::process::id()).rotate_left(32)
+}
+
+/// Runs an oracle command with a pair on its standard input, returning its exit status,
+/// or `None` if it was killed by a signal
+fn run_oracle(oracle: &[String], pair: &Pair) -> io::Result<Option<i32>> {
+    let mut child = Command::new(&oracle[0])
+        .args(&oracle[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    let written = writeln!(stdin, "{}\n{}", pair.left(), pair.right());
+    drop(stdin);
+    let status = child.wait()?;
+
+    // The oracle need not read the whole pair before exiting
+    match written {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e),
+        _ => Ok(status.code()),
+    }
+}
+
+fn invalid(context: &str, e: impl fmt::Display) -> Error {
+    Error::Invalid(format!("{context}: {e}"))
+}
+
+/// Opens the file named by the only positional argument, or standard input if it is
+/// `-` or missing, returning the name to report errors against and a buffered reader
+fn open_input(args: &Args) -> Result<(String, Box<dyn BufRead>), Error> {
+    let path = args.positional.first().map_or("-", String::as_str);
+
+    if path == "-" {
+        return Ok((path.to_string(), Box::new(io::stdin().lock())));
+    }
+
+    match fs::File::open(path) {
+        Ok(file) => Ok((path.to_string(), Box::new(io::BufReader::new(file)))),
+        Err(e) => Err(Error::Io(path.to_string(), e)),
+    }
+}
+
+/// The whole of an input, as bytes that need not be valid UTF-8
+enum Input {
+    Read(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl AsRef<[u8]> for Input {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Read(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map) => map,
+        }
+    }
+}
+
+/// Reads the file named by the only positional argument, or standard input if it is
+/// `-` or missing, returning the name to report errors against and the contents.
+/// With `--mmap`, a file is mapped into memory instead.
+fn read_input(args: &Args) -> Result<(String, Input), Error> {
+    let path = args.positional.first().map_or("-", String::as_str);
+    let mut contents = Vec::new();
+
+    let read = if path == "-" {
+        io::stdin()
+            .lock()
+            .read_to_end(&mut contents)
+            .map(|_| Input::Read(contents))
+    } else if args.flag("--mmap") {
+        map_input(path)?
+    } else {
+        fs::read(path).map(Input::Read)
+    };
+
+    match read {
+        Ok(input) => Ok((path.to_string(), input)),
+        Err(e) => Err(Error::Io(path.to_string(), e)),
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn map_input(path: &str) -> Result<io::Result<Input>, Error> {
+    let map = fs::File::open(path).and_then(|file| {
+        // SAFETY: the map is only read, and the usage text warns that the file must not
+        // be changed while it is mapped
+        unsafe { memmap2::Mmap::map(&file) }
+    });
+
+    Ok(map.map(Input::Mapped))
+}
+
+#[cfg(not(feature = "mmap"))]
+fn map_input(_: &str) -> Result<io::Result<Input>, Error> {
+    Err(Error::Usage(
+        "option `--mmap` needs the `mmap` feature".to_string(),
+    ))
+}
+
+/// The arguments after the command: `--flag`s, `--option value`s (or `--option=value`),
+/// and up to a fixed number of positional arguments
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(
+        args: &[String],
+        flags: &[&str],
+        valued: &[&str],
+        max_positional: usize,
+    ) -> Result<Self, Error> {
+        let mut parsed = Args {
+            positional: Vec::new(),
+            options: Vec::new(),
+        };
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if arg.starts_with("--") => (name, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+
+            if flags.contains(&name) && inline.is_none() {
+                parsed.options.push((name.to_string(), None));
+            } else if valued.contains(&name) {
+                let value = inline
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| Error::Usage(format!("option `{name}` needs a value")))?;
+                parsed.options.push((name.to_string(), Some(value)));
+            } else if arg.starts_with('-') && arg != "-" {
+                return Err(Error::Usage(format!("unknown option `{arg}`")));
+            } else if parsed.positional.len() < max_positional {
+                parsed.positional.push(arg.clone());
+            } else {
+                return Err(Error::Usage(format!("unexpected argument `{arg}`")));
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Reads a number of bytes, which may end in `K`, `M` or `G` for binary multiples
+    fn size(&self, name: &str, default: usize) -> Result<usize, Error> {
+        let Some(value) = self.value(name) else {
+            return Ok(default);
+        };
+
+        let (digits, shift) = match value.as_bytes().last() {
+            Some(b'K' | b'k') => (&value[..value.len() - 1], 10),
+            Some(b'M' | b'm') => (&value[..value.len() - 1], 20),
+            Some(b'G' | b'g') => (&value[..value.len() - 1], 30),
+            _ => (value, 0),
+        };
+
+        digits
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_mul(1 << shift))
+            .ok_or_else(|| Error::Usage(format!("option `{name}` needs a size, not `{value}`")))
+    }
+
+    fn number<N: FromStr>(&self, name: &str, default: N) -> Result<N, Error> {
+        self.value(name).map_or(Ok(default), |value| {
+            value
+                .parse()
+                .map_err(|_| Error::Usage(format!("option `{name}` needs a number, not `{value}`")))
+        })
+    }
+
+    fn probability(&self, name: &str, default: f64) -> Result<f64, Error> {
+        match self.number(name, default)? {
+            p if (0.0..=1.0).contains(&p) => Ok(p),
+            p => Err(Error::Usage(format!(
+                "option `{name}` needs a probability from 0 to 1, not `{p}`"
+            ))),
+        }
+    }
 }
@@ -0,0 +1,64 @@
+use rayon::prelude::*;
+
+use crate::{blocks, parse_block, PacketInt, Pair, PairParseError};
+
+/// Like [`sum_correct`](crate::sum_correct), but parsing and comparing the pairs across
+/// rayon's thread pool
+///
+/// The result is always the same as the serial version's. If several pairs are
+/// malformed, the first one in the input is reported.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{sum_correct, sum_correct_par};
+///
+/// let input = "[1]\n[2]\n\n[[3]]\n[2]\n\n[]\n[[]]\n".repeat(1000);
+/// assert_eq!(sum_correct_par(&input), sum_correct(&input));
+///
+/// let err = sum_correct_par("[1]\n[2]\n\n[3]\n\n[4,\n[5]\n").unwrap_err();
+/// assert_eq!((err.block, err.line), (2, 4));
+/// ```
+pub fn sum_correct_par(input: &(impl AsRef<[u8]> + ?Sized)) -> Result<usize, PairParseError> {
+    sum_correct_par_as::<u8>(input)
+}
+
+/// Like [`sum_correct_par`], but reading integers into `T` rather than `u8`
+pub fn sum_correct_par_as<T: PacketInt + Send>(
+    input: &(impl AsRef<[u8]> + ?Sized),
+) -> Result<usize, PairParseError> {
+    let verdicts: Vec<_> = blocks(input.as_ref())
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .enumerate()
+        .map(|block| parse_block::<T>(block).map(|pair| pair.is_in_order()))
+        .collect();
+
+    let mut sum = 0;
+    for (i, in_order) in verdicts.into_iter().enumerate() {
+        if in_order? {
+            sum += i + 1;
+        }
+    }
+
+    Ok(sum)
+}
+
+/// Like [`parse_pairs`](crate::parse_pairs), but parsing the pairs across rayon's
+/// thread pool. The pairs are returned in input order.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{parse_pairs, parse_pairs_par};
+///
+/// let input = "[1]\n[2]\n\n[3]\n\n[5]\n[4]";
+/// let serial: Vec<_> = parse_pairs::<u8>(input).collect();
+/// assert_eq!(parse_pairs_par::<u8>(input), serial);
+/// ```
+pub fn parse_pairs_par<T: PacketInt + Send>(
+    input: &(impl AsRef<[u8]> + ?Sized),
+) -> Vec<Result<Pair<T>, PairParseError>> {
+    blocks(input.as_ref())
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .enumerate()
+        .map(parse_block)
+        .collect()
+}
@@ -0,0 +1,377 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{Packet, PacketInt};
+
+/// A location in the parsed input
+///
+/// `offset` is a 0-based byte offset, `line` and `column` are 1-based and columns count bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    fn locate(src: &[u8], offset: usize) -> Self {
+        let before = &src[..offset];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+
+        Position {
+            offset,
+            line: before.iter().filter(|&&b| b == b'\n').count() + 1,
+            column: offset - line_start + 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// A JSON value that is valid JSON but has no packet equivalent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonType {
+    String,
+    Object,
+    Bool,
+    Null,
+    Float,
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::String => "string",
+            Self::Object => "object",
+            Self::Bool => "boolean",
+            Self::Null => "null",
+            Self::Float => "non-integer number",
+        })
+    }
+}
+
+/// The reasons a packet can fail to parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketParseError {
+    /// A byte (or the end of input, if `found` is `None`) that cannot appear at this point
+    UnexpectedToken {
+        found: Option<u8>,
+        expected: &'static str,
+        position: Position,
+    },
+    /// A `]` with no matching `[`, or a `[` that is never closed
+    UnbalancedBracket { position: Position },
+    /// An integer that does not fit in the packet's integer type
+    IntegerOverflow { position: Position },
+    /// Anything other than whitespace after a complete packet
+    TrailingInput { position: Position },
+    /// A JSON value other than a list or an integer
+    UnsupportedJsonType { found: JsonType, position: Position },
+}
+
+impl PacketParseError {
+    /// Where in the input the error was detected
+    pub fn position(&self) -> Position {
+        match self {
+            Self::UnexpectedToken { position, .. }
+            | Self::UnbalancedBracket { position }
+            | Self::IntegerOverflow { position }
+            | Self::TrailingInput { position }
+            | Self::UnsupportedJsonType { position, .. } => *position,
+        }
+    }
+}
+
+impl fmt::Display for PacketParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedToken {
+                found: Some(b),
+                expected,
+                position,
+            } => {
+                if b.is_ascii_graphic() {
+                    write!(
+                        f,
+                        "unexpected `{}` at {position}, expected {expected}",
+                        *b as char
+                    )
+                } else {
+                    write!(
+                        f,
+                        "unexpected byte {b:#04x} at {position}, expected {expected}"
+                    )
+                }
+            }
+            Self::UnexpectedToken {
+                found: None,
+                expected,
+                position,
+            } => write!(
+                f,
+                "unexpected end of input at {position}, expected {expected}"
+            ),
+            Self::UnbalancedBracket { position } => write!(f, "unbalanced bracket at {position}"),
+            Self::IntegerOverflow { position } => write!(f, "integer out of range at {position}"),
+            Self::TrailingInput { position } => write!(f, "trailing input at {position}"),
+            Self::UnsupportedJsonType { found, position } => {
+                write!(f, "unsupported {found} at {position}")
+            }
+        }
+    }
+}
+
+impl Error for PacketParseError {}
+
+impl PacketParseError {
+    /// Moves the position of this error from the start of a line to `lines` lines and
+    /// `bytes` bytes further into the input
+    pub(crate) fn shifted(mut self, lines: usize, bytes: usize) -> Self {
+        let (Self::UnexpectedToken { position, .. }
+        | Self::UnbalancedBracket { position }
+        | Self::IntegerOverflow { position }
+        | Self::TrailingInput { position }
+        | Self::UnsupportedJsonType { position, .. }) = &mut self;
+
+        position.line += lines;
+        position.offset += bytes;
+        self
+    }
+}
+
+/// What was wrong with a block of input that should have held a pair of packets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairErrorKind {
+    /// The block has no lines at all
+    MissingLeft,
+    /// The block has only one line
+    MissingRight,
+    /// The block has more than two lines
+    ExtraLines,
+    /// One of the two lines is not a valid packet
+    InvalidPacket(PacketParseError),
+}
+
+/// A block of input that could not be read as a [`Pair`](crate::Pair)
+///
+/// `block` is the 1-based index of the pair in the input and `line` the 1-based line
+/// of the input where the problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairParseError {
+    pub block: usize,
+    pub line: usize,
+    pub kind: PairErrorKind,
+}
+
+impl PairParseError {
+    /// Moves this error from the first block of the input to the given block,
+    /// which starts after `lines` lines and `bytes` bytes
+    pub(crate) fn in_block(mut self, block: usize, lines: usize, bytes: usize) -> Self {
+        self.block = block;
+        self.line += lines;
+        if let PairErrorKind::InvalidPacket(e) = self.kind {
+            self.kind = PairErrorKind::InvalidPacket(e.shifted(lines, bytes));
+        }
+        self
+    }
+}
+
+impl fmt::Display for PairParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pair {}: ", self.block)?;
+
+        match &self.kind {
+            PairErrorKind::MissingLeft => write!(f, "missing left packet at line {}", self.line),
+            PairErrorKind::MissingRight => {
+                write!(f, "missing right packet after line {}", self.line)
+            }
+            PairErrorKind::ExtraLines => write!(f, "unexpected extra line {}", self.line),
+            PairErrorKind::InvalidPacket(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for PairParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            PairErrorKind::InvalidPacket(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Parses exactly one packet, surrounded by optional whitespace
+pub(crate) fn parse<T: PacketInt>(src: &[u8]) -> Result<Packet<T>, PacketParseError> {
+    let mut parser = Parser { src, pos: 0 };
+
+    let packet = parser.packet()?;
+    parser.skip_whitespace();
+
+    match parser.peek() {
+        None => Ok(packet),
+        Some(b']') => Err(PacketParseError::UnbalancedBracket {
+            position: parser.position(parser.pos),
+        }),
+        Some(_) => Err(PacketParseError::TrailingInput {
+            position: parser.position(parser.pos),
+        }),
+    }
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        Position::locate(self.src, offset)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses a single value. Open lists are kept on an explicit stack of
+    /// `(offset of '[', items so far)` so nesting depth is limited only by memory.
+    fn packet<T: PacketInt>(&mut self) -> Result<Packet<T>, PacketParseError> {
+        let mut open: Vec<(usize, Vec<Packet<T>>)> = Vec::new();
+
+        loop {
+            let mut value = match self.value(open.last().map(|(at, _)| *at))? {
+                Some(value) => value,
+                None => {
+                    open.push((self.pos - 1, Vec::new()));
+                    continue;
+                }
+            };
+
+            // Attach the finished value to its parent, closing every list that ends here
+            loop {
+                let Some((at, items)) = open.last_mut() else {
+                    return Ok(value);
+                };
+                items.push(value);
+                self.skip_whitespace();
+
+                match self.peek() {
+                    Some(b',') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some(b']') => {
+                        self.pos += 1;
+                        let (_, items) = open.pop().unwrap();
+                        value = Packet::List(items);
+                    }
+                    None => {
+                        return Err(PacketParseError::UnbalancedBracket {
+                            position: self.position(*at),
+                        })
+                    }
+                    found => {
+                        return Err(PacketParseError::UnexpectedToken {
+                            found,
+                            expected: "`,` or `]`",
+                            position: self.position(self.pos),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses the start of a value. Returns `None` after consuming the `[` of a
+    /// non-empty list, whose items the caller must then parse. `open` is the
+    /// offset of the enclosing `[`, if any.
+    fn value<T: PacketInt>(
+        &mut self,
+        open: Option<usize>,
+    ) -> Result<Option<Packet<T>>, PacketParseError> {
+        self.skip_whitespace();
+
+        let start = self.pos;
+        let unsupported = |found| PacketParseError::UnsupportedJsonType {
+            found,
+            position: self.position(start),
+        };
+
+        match self.peek() {
+            Some(b'[') => {
+                self.pos += 1;
+                self.skip_whitespace();
+
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    Ok(Some(Packet::List(Vec::new())))
+                } else {
+                    self.pos = start + 1;
+                    Ok(None)
+                }
+            }
+            Some(b'0'..=b'9' | b'-') => self.int().map(Some),
+            Some(b'"') => Err(unsupported(JsonType::String)),
+            Some(b'{') => Err(unsupported(JsonType::Object)),
+            Some(b't' | b'f') => Err(unsupported(JsonType::Bool)),
+            Some(b'n') => Err(unsupported(JsonType::Null)),
+            Some(b']') if open.is_none() => Err(PacketParseError::UnbalancedBracket {
+                position: self.position(start),
+            }),
+            None if open.is_some() => Err(PacketParseError::UnbalancedBracket {
+                position: self.position(open.unwrap()),
+            }),
+            found => Err(PacketParseError::UnexpectedToken {
+                found,
+                expected: "a list or an integer",
+                position: self.position(start),
+            }),
+        }
+    }
+
+    fn int<T: PacketInt>(&mut self) -> Result<Packet<T>, PacketParseError> {
+        let start = self.pos;
+        let negative = self.peek() == Some(b'-');
+        if negative {
+            self.pos += 1;
+        }
+
+        let digits = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+
+        if self.pos == digits {
+            return Err(PacketParseError::UnexpectedToken {
+                found: self.peek(),
+                expected: "a digit",
+                position: self.position(self.pos),
+            });
+        }
+
+        if matches!(self.peek(), Some(b'.' | b'e' | b'E')) {
+            return Err(PacketParseError::UnsupportedJsonType {
+                found: JsonType::Float,
+                position: self.position(start),
+            });
+        }
+
+        T::from_digits(negative, &self.src[digits..self.pos])
+            .map(Packet::Int)
+            .ok_or_else(|| PacketParseError::IntegerOverflow {
+                position: self.position(start),
+            })
+    }
+}
@@ -0,0 +1,494 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{path_string, Packet, PacketInt};
+
+/// A query selecting values within a packet, such as `[0][*][-1]` or `..[?int > 5]`
+///
+/// A query is a series of steps, each applied to every value selected so far, starting
+/// from the packet itself:
+///
+/// - `[N]` selects the item at index `N` of each list, counting back from the end if
+///   `N` is negative
+/// - `[*]` selects every item of each list
+/// - `[?CONDITION]` selects every item of each list that meets the condition
+/// - `..` selects each value and every value within it
+/// - `depth(N)` selects every value nested `N` lists within each value
+///
+/// A condition is made of tests joined by `&&` and `||`, where `&&` binds tighter. Each
+/// test may be negated with `!`:
+///
+/// - `int` and `list` test what kind of value an item is
+/// - `int OP N` tests an integer against `N`
+/// - `len OP N` tests the length of a list
+/// - `depth OP N` tests how many lists enclose an item within the whole packet
+///
+/// where `OP` is one of `==`, `!=`, `<`, `<=`, `>` and `>=`. An integer is never a list,
+/// so `len` is false for integers and comparing `int` is false for lists. Integers are
+/// not promoted to lists: indexing an integer selects nothing.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{Packet, Query};
+///
+/// let packet: Packet = "[[[1,2],[3]],[4],[[10],7]]".parse().unwrap();
+/// let select = |query: &str| -> Vec<String> {
+///     let query: Query = query.parse().unwrap();
+///     packet.select(&query).iter().map(|m| format!("{}={}", m.path_string(), m.value)).collect()
+/// };
+///
+/// assert_eq!(select("[0][*][-1]"), ["[0][0][1]=2", "[0][1][0]=3"]);
+/// assert_eq!(select("[*][-1]"), ["[0][1]=[3]", "[1][0]=4", "[2][1]=7"]);
+/// assert_eq!(select("..[?int > 5]"), ["[2][0][0]=10", "[2][1]=7"]);
+/// assert_eq!(select("[2]depth(2)"), ["[2][0][0]=10"]);
+/// assert_eq!(select("..[?int == 10 && depth == 3]"), ["[2][0][0]=10"]);
+/// assert_eq!(select("[?list && len >= 2 || int]"), ["[0]=[[1,2],[3]]", "[2]=[[10],7]"]);
+/// assert_eq!(select("[?!list]"), Vec::<String>::new());
+/// assert_eq!(select(""), ["=[[[1,2],[3]],[4],[[10],7]]"]);
+///
+/// let err = "[0][?int >]".parse::<Query>().unwrap_err();
+/// assert_eq!(err.to_string(), "unexpected `]` at column 11, expected an integer");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+    /// The numbers that integers are compared with, as a sign and decimal digits, to be
+    /// converted to the packet's integer type when the query is run
+    ints: Vec<(bool, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Index(isize),
+    All,
+    Filter(Condition),
+    Descendants,
+    Depth(usize),
+}
+
+/// Groups of tests joined by `||`, each group being tests joined by `&&`
+type Condition = Vec<Vec<Test>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Test {
+    negated: bool,
+    kind: TestKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TestKind {
+    Int,
+    List,
+    /// Compares an integer with the number at an index of [`Query::ints`]
+    IntIs(Op, usize),
+    Len(Op, usize),
+    Depth(Op, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    /// Whether a value ordered this way against the operand passes
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering.is_eq(),
+            Op::Ne => ordering.is_ne(),
+            Op::Lt => ordering.is_lt(),
+            Op::Le => ordering.is_le(),
+            Op::Gt => ordering.is_gt(),
+            Op::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// A number from a query converted to the packet's integer type. A number out of the
+/// type's range is beyond every integer in the packet.
+enum Operand<T> {
+    Int(T),
+    Below,
+    Above,
+}
+
+/// A value selected by a [`Query`], with the path to it from the packet
+#[derive(Debug, Clone)]
+pub struct Match<'a, T = u8> {
+    /// The index of the value within each enclosing list, outermost first
+    pub path: Vec<usize>,
+    pub value: &'a Packet<T>,
+}
+
+impl<T> Match<'_, T> {
+    /// The path written as indices, e.g. `[1][0][2]`
+    pub fn path_string(&self) -> String {
+        path_string(&self.path)
+    }
+}
+
+impl<T: PacketInt> Packet<T> {
+    /// Selects the values within this packet that the query finds, in the order they
+    /// appear in the packet. See [`Query`] for the syntax.
+    pub fn select<'a>(&'a self, query: &Query) -> Vec<Match<'a, T>> {
+        let operands: Vec<Operand<T>> = query
+            .ints
+            .iter()
+            .map(
+                |(negative, digits)| match T::from_digits(*negative, digits.as_bytes()) {
+                    Some(n) => Operand::Int(n),
+                    None if *negative => Operand::Below,
+                    None => Operand::Above,
+                },
+            )
+            .collect();
+        let mut selected = vec![Match {
+            path: Vec::new(),
+            value: self,
+        }];
+
+        for step in &query.steps {
+            let mut next = Vec::new();
+            for Match { path, value } in selected {
+                step.apply(path, value, &operands, &mut next);
+            }
+
+            // Back into the order of the packet. A value within another selected value
+            // can also be selected twice by `..`.
+            next.sort_by(|a, b| a.path.cmp(&b.path));
+            next.dedup_by(|a, b| a.path == b.path);
+            selected = next;
+        }
+
+        selected
+    }
+}
+
+impl Step {
+    /// Adds the values this step selects from a value to `next`
+    fn apply<'a, T: Ord>(
+        &self,
+        path: Vec<usize>,
+        value: &'a Packet<T>,
+        operands: &[Operand<T>],
+        next: &mut Vec<Match<'a, T>>,
+    ) {
+        let child = |i: usize, item| {
+            let mut path = path.clone();
+            path.push(i);
+            Match { path, value: item }
+        };
+
+        match (self, value) {
+            (Step::Index(i), Packet::List(items)) => {
+                let i = match usize::try_from(*i) {
+                    Ok(i) => Some(i),
+                    Err(_) => items.len().checked_sub(i.unsigned_abs()),
+                };
+                if let Some((i, item)) = i.and_then(|i| Some((i, items.get(i)?))) {
+                    next.push(child(i, item));
+                }
+            }
+            (Step::All, Packet::List(items)) => {
+                next.extend(items.iter().enumerate().map(|(i, item)| child(i, item)));
+            }
+            (Step::Filter(condition), Packet::List(items)) => {
+                let depth = path.len() + 1;
+                next.extend(
+                    items
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, item)| meets(condition, item, depth, operands))
+                        .map(|(i, item)| child(i, item)),
+                );
+            }
+            (Step::Descendants, _) => select_below(path, value, None, next),
+            (Step::Depth(depth), _) => select_below(path, value, Some(*depth), next),
+            (Step::Index(_) | Step::All | Step::Filter(_), Packet::Int(_)) => (),
+        }
+    }
+}
+
+/// Adds a value and every value within it to `next` in pre-order, or only those
+/// nested exactly `depth` lists within it
+fn select_below<'a, T>(
+    path: Vec<usize>,
+    value: &'a Packet<T>,
+    depth: Option<usize>,
+    next: &mut Vec<Match<'a, T>>,
+) {
+    let mut stack = vec![(path, value, 0)];
+
+    while let Some((path, value, below)) = stack.pop() {
+        if let Packet::List(items) = value {
+            if depth.is_none_or(|depth| below < depth) {
+                // Pushed in reverse so that items are visited in order
+                for (i, item) in items.iter().enumerate().rev() {
+                    let mut path = path.clone();
+                    path.push(i);
+                    stack.push((path, item, below + 1));
+                }
+            }
+        }
+
+        if depth.is_none_or(|depth| below == depth) {
+            next.push(Match { path, value });
+        }
+    }
+}
+
+fn meets<T: Ord>(
+    condition: &Condition,
+    value: &Packet<T>,
+    depth: usize,
+    operands: &[Operand<T>],
+) -> bool {
+    let passes = |test: &Test| {
+        let result = match (&test.kind, value) {
+            (TestKind::Int, _) => matches!(value, Packet::Int(_)),
+            (TestKind::List, _) => matches!(value, Packet::List(_)),
+            (TestKind::IntIs(op, i), Packet::Int(n)) => op.holds(match &operands[*i] {
+                Operand::Int(operand) => n.cmp(operand),
+                Operand::Below => Ordering::Greater,
+                Operand::Above => Ordering::Less,
+            }),
+            (TestKind::Len(op, len), Packet::List(items)) => op.holds(items.len().cmp(len)),
+            (TestKind::Depth(op, n), _) => op.holds(depth.cmp(n)),
+            (TestKind::IntIs(..), Packet::List(_)) | (TestKind::Len(..), Packet::Int(_)) => false,
+        };
+        result != test.negated
+    };
+
+    condition.iter().any(|all| all.iter().all(passes))
+}
+
+/// The reasons a query can fail to parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryParseError {
+    /// A character (or the end of the query, if `found` is `None`) that cannot appear
+    /// at this point
+    UnexpectedToken {
+        found: Option<char>,
+        expected: &'static str,
+        offset: usize,
+    },
+    /// An index or number that is too large
+    NumberOutOfRange { offset: usize },
+}
+
+impl QueryParseError {
+    /// The 0-based byte offset in the query where the error was detected
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::UnexpectedToken { offset, .. } | Self::NumberOutOfRange { offset } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let column = self.offset() + 1;
+
+        match self {
+            Self::UnexpectedToken {
+                found: Some(c),
+                expected,
+                ..
+            } => write!(
+                f,
+                "unexpected `{c}` at column {column}, expected {expected}"
+            ),
+            Self::UnexpectedToken {
+                found: None,
+                expected,
+                ..
+            } => write!(f, "unexpected end of query, expected {expected}"),
+            Self::NumberOutOfRange { .. } => write!(f, "number out of range at column {column}"),
+        }
+    }
+}
+
+impl Error for QueryParseError {}
+
+impl FromStr for Query {
+    type Err = QueryParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            query: s,
+            pos: 0,
+            ints: Vec::new(),
+        };
+        let mut steps = Vec::new();
+
+        while parser.peek().is_some() {
+            let step = if parser.eat("[") {
+                let step = if parser.eat("*") {
+                    Step::All
+                } else if parser.eat("?") {
+                    Step::Filter(parser.condition()?)
+                } else {
+                    let offset = parser.offset();
+                    let (negative, digits) = parser.int("an index, `*` or `?`")?;
+                    let index = format!("{}{digits}", if negative { "-" } else { "" });
+                    Step::Index(
+                        index
+                            .parse()
+                            .map_err(|_| QueryParseError::NumberOutOfRange { offset })?,
+                    )
+                };
+                parser.expect("]", "`]`")?;
+                step
+            } else if parser.eat("..") {
+                Step::Descendants
+            } else if parser.eat("depth") {
+                parser.expect("(", "`(`")?;
+                let depth = parser.number()?;
+                parser.expect(")", "`)`")?;
+                Step::Depth(depth)
+            } else {
+                return Err(parser.unexpected("`[`, `..` or `depth(`"));
+            };
+            steps.push(step);
+        }
+
+        Ok(Query {
+            steps,
+            ints: parser.ints,
+        })
+    }
+}
+
+struct Parser<'a> {
+    query: &'a str,
+    pos: usize,
+    ints: Vec<(bool, String)>,
+}
+
+impl Parser<'_> {
+    /// The next character that is not whitespace
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.query[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        rest.trim_start().chars().next()
+    }
+
+    fn offset(&mut self) -> usize {
+        self.peek();
+        self.pos
+    }
+
+    /// Consumes `token` if it comes next
+    fn eat(&mut self, token: &str) -> bool {
+        self.peek();
+        let found = self.query[self.pos..].starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str, expected: &'static str) -> Result<(), QueryParseError> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.unexpected(expected)),
+        }
+    }
+
+    fn unexpected(&mut self, expected: &'static str) -> QueryParseError {
+        QueryParseError::UnexpectedToken {
+            found: self.peek(),
+            expected,
+            offset: self.pos,
+        }
+    }
+
+    /// Reads an optional `-` and a run of digits
+    fn int(&mut self, expected: &'static str) -> Result<(bool, String), QueryParseError> {
+        let negative = self.eat("-");
+        let rest = &self.query[self.pos..];
+        let len = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if len == 0 {
+            return Err(self.unexpected(expected));
+        }
+
+        self.pos += len;
+        Ok((negative, rest[..len].to_string()))
+    }
+
+    fn number(&mut self) -> Result<usize, QueryParseError> {
+        let offset = self.offset();
+        match self.int("a number")? {
+            (false, digits) => digits
+                .parse()
+                .map_err(|_| QueryParseError::NumberOutOfRange { offset }),
+            (true, _) => Err(QueryParseError::UnexpectedToken {
+                found: Some('-'),
+                expected: "a number",
+                offset,
+            }),
+        }
+    }
+
+    fn op(&mut self) -> Result<Op, QueryParseError> {
+        // Longer operators first, so that `<=` is not read as `<`
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+
+        match ops.into_iter().find(|(token, _)| self.eat(token)) {
+            Some((_, op)) => Ok(op),
+            None => Err(self.unexpected("a comparison")),
+        }
+    }
+
+    /// Reads a condition up to the closing `]`
+    fn condition(&mut self) -> Result<Condition, QueryParseError> {
+        let mut condition = vec![Vec::new()];
+
+        loop {
+            let mut negated = false;
+            while self.eat("!") {
+                negated = !negated;
+            }
+
+            let kind = if self.eat("int") {
+                match self.peek() {
+                    Some('=' | '!' | '<' | '>') => {
+                        let op = self.op()?;
+                        let int = self.int("an integer")?;
+                        self.ints.push(int);
+                        TestKind::IntIs(op, self.ints.len() - 1)
+                    }
+                    _ => TestKind::Int,
+                }
+            } else if self.eat("list") {
+                TestKind::List
+            } else if self.eat("len") {
+                TestKind::Len(self.op()?, self.number()?)
+            } else if self.eat("depth") {
+                TestKind::Depth(self.op()?, self.number()?)
+            } else {
+                return Err(self.unexpected("`int`, `list`, `len` or `depth`"));
+            };
+            condition.last_mut().unwrap().push(Test { negated, kind });
+
+            if self.eat("||") {
+                condition.push(Vec::new());
+            } else if !self.eat("&&") {
+                return Ok(condition);
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::marker::PhantomData;
+
+use crate::{Packet, PacketInt, Pair, PairErrorKind, PairParseError};
+
+/// The reasons [`PairReader`] can fail to produce a pair
+#[derive(Debug)]
+pub enum ReadError {
+    /// The underlying reader failed. No more pairs will be read.
+    Io(io::Error),
+    /// A block of input was not a valid pair. Reading carries on with the next block.
+    Parse(PairParseError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<PairParseError> for ReadError {
+    fn from(e: PairParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// Reads pairs lazily from any buffered source, holding only the current block in memory
+///
+/// Yields each pair with its 1-based index. Blocks may be separated by any number of
+/// blank lines, and lines may end in CRLF or carry trailing whitespace.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{PairReader, ReadError};
+///
+/// let input = "[1]\r\n[2]  \r\n\r\n\r\n\r\n[[1]]\r\n[3,\r\n\r\n[3]\r\n[1]\r\n";
+/// let mut pairs = PairReader::new(input.as_bytes());
+///
+/// let (index, pair) = pairs.next().unwrap().unwrap();
+/// assert_eq!(index, 1);
+/// assert!(pair.is_in_order());
+///
+/// let Some(Err(ReadError::Parse(err))) = pairs.next() else { panic!() };
+/// assert_eq!((err.block, err.line), (2, 7));
+///
+/// let (index, pair) = pairs.next().unwrap().unwrap();
+/// assert_eq!(index, 3);
+/// assert!(!pair.is_in_order());
+///
+/// assert!(pairs.next().is_none());
+/// ```
+pub struct PairReader<R, T = u8> {
+    reader: R,
+    line: Vec<u8>,
+    /// Lines and bytes read so far
+    lines: usize,
+    bytes: usize,
+    /// Pairs read so far, including malformed ones
+    index: usize,
+    done: bool,
+    int: PhantomData<T>,
+}
+
+impl<R: BufRead> PairReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_int_type(reader)
+    }
+}
+
+impl<R: BufRead, T: PacketInt> PairReader<R, T> {
+    /// Like [`PairReader::new`], but reading integers into `T` rather than `u8`
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::PairReader;
+    ///
+    /// let mut pairs = PairReader::<_, i32>::with_int_type("[-1000]\n[1]\n".as_bytes());
+    /// assert!(pairs.next().unwrap().unwrap().1.is_in_order());
+    /// ```
+    pub fn with_int_type(reader: R) -> Self {
+        PairReader {
+            reader,
+            line: Vec::new(),
+            lines: 0,
+            bytes: 0,
+            index: 0,
+            done: false,
+            int: PhantomData,
+        }
+    }
+
+    /// Reads the next line into `self.line`, returning whether it holds anything
+    /// but whitespace, or `None` at the end of input
+    fn read_line(&mut self) -> io::Result<Option<bool>> {
+        self.line.clear();
+        self.bytes += self.reader.read_until(b'\n', &mut self.line)?;
+
+        if self.line.is_empty() {
+            return Ok(None);
+        }
+
+        self.lines += 1;
+        Ok(Some(!self.line.iter().all(u8::is_ascii_whitespace)))
+    }
+
+    /// Parses the line just read, as line `self.lines` of the input
+    fn parse_line(&self) -> Result<Packet<T>, PairParseError> {
+        let start = self.bytes - self.line.len();
+
+        Packet::from_bytes(&self.line).map_err(|e| PairParseError {
+            block: self.index,
+            line: self.lines,
+            kind: PairErrorKind::InvalidPacket(e.shifted(self.lines - 1, start)),
+        })
+    }
+
+    fn read_pair(&mut self) -> Result<Option<Pair<T>>, ReadError> {
+        // Skip the blank lines before the block
+        loop {
+            match self.read_line()? {
+                None => return Ok(None),
+                Some(true) => break,
+                Some(false) => (),
+            }
+        }
+
+        self.index += 1;
+        let error = |block, line, kind| PairParseError { block, line, kind };
+
+        let left = self.parse_line();
+        let left_line = self.lines;
+        let (right, ended) = match self.read_line()? {
+            Some(true) => (self.parse_line(), false),
+            _ => {
+                let missing = error(self.index, left_line, PairErrorKind::MissingRight);
+                (Err(missing), true)
+            }
+        };
+
+        // Read the rest of the block even if it is malformed, so the next starts cleanly
+        let mut extra = None;
+        if !ended {
+            while let Some(true) = self.read_line()? {
+                extra.get_or_insert(self.lines);
+            }
+        }
+
+        let pair = Pair {
+            left: left?,
+            right: right?,
+        };
+        match extra {
+            Some(line) => Err(error(self.index, line, PairErrorKind::ExtraLines).into()),
+            None => Ok(Some(pair)),
+        }
+    }
+}
+
+impl<R: BufRead, T: PacketInt> Iterator for PairReader<R, T> {
+    type Item = Result<(usize, Pair<T>), ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.read_pair() {
+            Ok(Some(pair)) => Some(Ok((self.index, pair))),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = matches!(e, ReadError::Io(_));
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use serde::de::{
+    self, Deserialize, Deserializer, EnumAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
+};
+use serde::ser::{Error as _, Serialize, SerializeSeq, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::{BigInt, Packet, PacketInt};
+
+// These impls recurse once per level of nesting, as serde's data model requires, so
+// unlike parsing, comparing or formatting a packet, they are limited in depth by the
+// call stack and by any recursion limit of the format being used.
+//
+// Human-readable formats get the puzzle's own syntax: a number or a sequence. Binary
+// formats such as bincode cannot tell those apart when reading, so there a packet is an
+// enum whose `Int` variant holds the integer's decimal digits, which works for every
+// `PacketInt`, and whose `List` variant holds the items.
+
+const VARIANTS: &[&str] = &["Int", "List"];
+
+impl<T: Serialize + fmt::Display> Serialize for Packet<T> {
+    /// Writes an integer as a number and a list as a sequence, or in a binary format, as
+    /// an `Int` or `List` variant
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    ///
+    /// let packet: Packet = "[1,[2,[]]]".parse().unwrap();
+    /// assert_eq!(serde_json::to_string(&packet).unwrap(), "[1,[2,[]]]");
+    /// ```
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Packet::Int(n) if !serializer.is_human_readable() => {
+                serializer.serialize_newtype_variant("Packet", 0, "Int", &n.to_string())
+            }
+            Packet::List(items) if !serializer.is_human_readable() => {
+                serializer.serialize_newtype_variant("Packet", 1, "List", items)
+            }
+            Packet::Int(n) => n.serialize(serializer),
+            Packet::List(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+impl<'de, T: PacketInt> Deserialize<'de> for Packet<T> {
+    /// Reads a number as an integer and a sequence as a list, or in a binary format, the
+    /// variants written by [`Serialize`]
+    /// # Examples
+    /// ```
+    /// use advent_of_code_2022_13::Packet;
+    ///
+    /// let packet: Packet = serde_json::from_str("[1, [2, []]]").unwrap();
+    /// assert!(packet.identical(&"[1,[2,[]]]".parse().unwrap()));
+    ///
+    /// assert!(serde_json::from_str::<Packet>("[256]").is_err());
+    /// assert!(serde_json::from_str::<Packet<u16>>("[256]").is_ok());
+    /// assert!(serde_json::from_str::<Packet>("[true]").is_err());
+    /// ```
+    ///
+    /// Binary formats, which cannot say what kind of value comes next, work too
+    /// ```
+    /// use advent_of_code_2022_13::{BigInt, Packet};
+    ///
+    /// let packet: Packet<BigInt> = "[1,[-20,[]],340282366920938463463374607431768211456]"
+    ///     .parse()
+    ///     .unwrap();
+    /// let bytes = bincode::serialize(&packet).unwrap();
+    /// assert!(bincode::deserialize::<Packet<BigInt>>(&bytes).unwrap().identical(&packet));
+    ///
+    /// let bytes = bincode::serialize(&Packet::<u16>::Int(256)).unwrap();
+    /// assert!(bincode::deserialize::<Packet>(&bytes).is_err());
+    /// ```
+    ///
+    /// Formats that carry 128-bit integers can fill packets with wide integer types
+    /// ```
+    /// use advent_of_code_2022_13::{BigInt, Packet};
+    /// use serde::de::{value, Deserialize, IntoDeserializer};
+    ///
+    /// let max: value::U128Deserializer<value::Error> = u128::MAX.into_deserializer();
+    /// let min: value::I128Deserializer<value::Error> = i128::MIN.into_deserializer();
+    ///
+    /// let packet = Packet::<BigInt>::deserialize(max).unwrap();
+    /// assert!(packet.identical(&Packet::Int(u128::MAX.to_string().parse().unwrap())));
+    /// let packet = Packet::<i128>::deserialize(min).unwrap();
+    /// assert!(packet.identical(&Packet::Int(i128::MIN)));
+    /// assert!(Packet::<u64>::deserialize(max).is_err());
+    /// ```
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PacketVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_enum("Packet", VARIANTS, PacketVisitor(PhantomData))
+        }
+    }
+}
+
+struct PacketVisitor<T>(PhantomData<T>);
+
+impl<'de, T: PacketInt> Visitor<'de> for PacketVisitor<T> {
+    type Value = Packet<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a packet integer or a list of packets")
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+        T::from_u64(n)
+            .map(Packet::Int)
+            .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(n), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+        T::from_i64(n)
+            .map(Packet::Int)
+            .ok_or_else(|| E::invalid_value(Unexpected::Signed(n), &self))
+    }
+
+    fn visit_u128<E: de::Error>(self, n: u128) -> Result<Self::Value, E> {
+        T::from_digits(false, n.to_string().as_bytes())
+            .map(Packet::Int)
+            .ok_or_else(|| E::invalid_value(Unexpected::Other(&n.to_string()), &self))
+    }
+
+    fn visit_i128<E: de::Error>(self, n: i128) -> Result<Self::Value, E> {
+        T::from_digits(n < 0, n.unsigned_abs().to_string().as_bytes())
+            .map(Packet::Int)
+            .ok_or_else(|| E::invalid_value(Unexpected::Other(&n.to_string()), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Packet::List(items))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        match data.variant()? {
+            (Variant::Int, int) => {
+                let digits: String = int.newtype_variant()?;
+                let (negative, magnitude) = match digits.strip_prefix('-') {
+                    Some(magnitude) => (true, magnitude),
+                    None => (false, digits.as_str()),
+                };
+
+                Some(magnitude)
+                    .filter(|m| !m.is_empty() && m.bytes().all(|d| d.is_ascii_digit()))
+                    .and_then(|m| T::from_digits(negative, m.as_bytes()))
+                    .map(Packet::Int)
+                    .ok_or_else(|| de::Error::invalid_value(Unexpected::Str(&digits), &self))
+            }
+            (Variant::List, list) => list.newtype_variant().map(Packet::List),
+        }
+    }
+}
+
+/// The variant of a packet in a binary format, identified by index or by name
+enum Variant {
+    Int,
+    List,
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(VariantVisitor)
+    }
+}
+
+struct VariantVisitor;
+
+impl Visitor<'_> for VariantVisitor {
+    type Value = Variant;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("`Int` or `List`")
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Variant, E> {
+        match n {
+            0 => Ok(Variant::Int),
+            1 => Ok(Variant::List),
+            _ => Err(E::invalid_value(Unexpected::Unsigned(n), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Variant, E> {
+        match name {
+            "Int" => Ok(Variant::Int),
+            "List" => Ok(Variant::List),
+            _ => Err(E::unknown_variant(name, VARIANTS)),
+        }
+    }
+}
+
+impl Serialize for BigInt {
+    /// Writes the integer as the smallest of `i64`, `u64`, `i128` or `u128` that holds it,
+    /// failing if none does
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let digits = self.to_string();
+
+        if let Ok(n) = digits.parse::<i64>() {
+            serializer.serialize_i64(n)
+        } else if let Ok(n) = digits.parse::<u64>() {
+            serializer.serialize_u64(n)
+        } else if let Ok(n) = digits.parse::<i128>() {
+            serializer.serialize_i128(n)
+        } else if let Ok(n) = digits.parse::<u128>() {
+            serializer.serialize_u128(n)
+        } else {
+            Err(S::Error::custom(format!(
+                "{digits} is too large to serialize"
+            )))
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{Packet, PacketInt, Pair};
+
+/// A change that makes one value within a packet smaller
+enum Edit<T> {
+    /// Remove `len` items from a list, starting at `start`
+    Remove { start: usize, len: usize },
+    /// Replace a list with its item at the index
+    Unwrap(usize),
+    /// Replace an integer with a smaller one
+    Lower(T),
+}
+
+/// Shrinks a pair to a smaller one that is still interesting, for finding the essence
+/// of a pair that two implementations disagree on
+///
+/// Delta-debugs both packets, trying larger changes first: removing runs of items from
+/// lists, replacing lists with one of their items, and moving integers towards zero.
+/// Every change that keeps the pair interesting is kept, until no single change does.
+/// `interesting` should hold for `pair` itself; if no smaller pair is interesting,
+/// `pair` is returned unchanged.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{shrink, Pair};
+///
+/// // A comparison that wrongly compares the text of the packets
+/// let disagrees = |pair: &Pair| {
+///     (pair.left().to_string() <= pair.right().to_string()) != pair.is_in_order()
+/// };
+///
+/// let pair = Pair::new("[[1],[2,3,4],[5,6]]\n[[1],4,7,[[8]]]");
+/// assert!(disagrees(&pair));
+///
+/// let shrunk = shrink(pair, disagrees);
+/// assert!(disagrees(&shrunk));
+/// assert_eq!(shrunk.left().to_string(), "[]");
+/// assert_eq!(shrunk.right().to_string(), "[[]]");
+/// ```
+pub fn shrink<T: PacketInt>(
+    pair: Pair<T>,
+    mut interesting: impl FnMut(&Pair<T>) -> bool,
+) -> Pair<T> {
+    let mut pair = pair;
+
+    'search: loop {
+        for left in [true, false] {
+            let packet = if left { &pair.left } else { &pair.right };
+
+            for (path, edit) in edits(packet) {
+                let mut candidate = pair.clone();
+                let packet = if left {
+                    &mut candidate.left
+                } else {
+                    &mut candidate.right
+                };
+                apply(packet.get_path_mut(&path).unwrap(), edit);
+
+                if interesting(&candidate) {
+                    pair = candidate;
+                    continue 'search;
+                }
+            }
+        }
+
+        return pair;
+    }
+}
+
+/// Every edit that shrinks the packet, with the path to the value it changes: removals
+/// from the outermost lists first, then unwrapping, then lowering integers
+fn edits<T: PacketInt>(packet: &Packet<T>) -> Vec<(Vec<usize>, Edit<T>)> {
+    let (mut removals, mut unwraps, mut lowerings) = (Vec::new(), Vec::new(), Vec::new());
+    let mut stack = vec![(Vec::new(), packet)];
+
+    while let Some((path, value)) = stack.pop() {
+        match value {
+            Packet::Int(n) => {
+                lowerings.extend(lower(n).into_iter().map(|n| (path.clone(), Edit::Lower(n))))
+            }
+            Packet::List(items) => {
+                let n = items.len();
+                let mut size = n;
+                while size > 0 {
+                    for start in (0..n).step_by(size) {
+                        let len = size.min(n - start);
+                        removals.push((path.clone(), Edit::Remove { start, len }));
+                    }
+                    size /= 2;
+                }
+                unwraps.extend((0..n).map(|i| (path.clone(), Edit::Unwrap(i))));
+
+                // Pushed in reverse so that items are visited in order
+                for (i, item) in items.iter().enumerate().rev() {
+                    let mut path = path.clone();
+                    path.push(i);
+                    stack.push((path, item));
+                }
+            }
+        }
+    }
+
+    removals.extend(unwraps);
+    removals.extend(lowerings);
+    removals
+}
+
+/// Integers closer to zero than `n`, as close as possible first: zero, then the
+/// magnitude less a half, a quarter and so on down to one, then `n` made positive
+fn lower<T: PacketInt>(n: &T) -> Vec<T> {
+    let n = n.to_string();
+    let (negative, digits) = match n.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, n.as_str()),
+    };
+    if digits == "0" {
+        return Vec::new();
+    }
+
+    let mut lower = vec!["0".to_string()];
+    match digits.parse::<u128>() {
+        Ok(magnitude) => {
+            let mut delta = magnitude / 2;
+            while delta > 0 {
+                lower.push((magnitude - delta).to_string());
+                delta /= 2;
+            }
+        }
+        // Too large to halve; dividing by ten will do
+        Err(_) => lower.push(digits[..digits.len() - 1].to_string()),
+    }
+    lower.dedup();
+
+    let mut lower: Vec<T> = lower
+        .iter()
+        .filter_map(|digits| T::from_digits(negative && digits != "0", digits.as_bytes()))
+        .collect();
+    if negative {
+        lower.extend(T::from_digits(false, digits.as_bytes()));
+    }
+    lower
+}
+
+fn apply<T>(value: &mut Packet<T>, edit: Edit<T>) {
+    match (edit, &mut *value) {
+        (Edit::Remove { start, len }, Packet::List(items)) => {
+            items.drain(start..start + len);
+        }
+        (Edit::Unwrap(i), Packet::List(items)) => *value = items.swap_remove(i),
+        (Edit::Lower(n), Packet::Int(_)) => *value = Packet::Int(n),
+        _ => unreachable!("edits are made for the kind of value they change"),
+    }
+}
@@ -0,0 +1,349 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, Write};
+use std::path::PathBuf;
+
+use crate::{Packet, PacketInt, PacketParseError};
+
+/// The most runs merged at once, to stay well within limits on open files
+const FAN_IN: usize = 64;
+
+/// The reasons [`ExternalSort`] can fail
+#[derive(Debug)]
+pub enum SortError {
+    /// Reading the input failed
+    Read(io::Error),
+    /// Writing the output failed
+    Write(io::Error),
+    /// Creating, writing or reading a temporary file failed
+    Temp(io::Error),
+    /// A line of input was not a valid packet. Its position is within the whole input.
+    Parse(PacketParseError),
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Read(e) | Self::Write(e) => write!(f, "{e}"),
+            Self::Temp(e) => write!(f, "temporary file: {e}"),
+            Self::Parse(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for SortError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Read(e) | Self::Write(e) | Self::Temp(e) => Some(e),
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<PacketParseError> for SortError {
+    fn from(e: PacketParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// Sorts packets, one per line, that may not all fit in memory
+///
+/// Packets are read into memory until they take up about the memory budget, then sorted
+/// and spilled to a temporary file. Once the input is exhausted the spilled runs are
+/// merged into the output. Input that fits in the budget never touches the disk.
+///
+/// The sort is stable, so packets that are equal but written differently, like `5`
+/// and `[5]`, keep their input order, and with [`unique`](Self::unique) the first of
+/// them is kept.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::ExternalSort;
+///
+/// let input = "[3]\n[[1],2]\n\n[1,2]\n[]\n3\n";
+/// let mut output = Vec::new();
+///
+/// let written = ExternalSort::new().memory(0).sort(input.as_bytes(), &mut output).unwrap();
+/// assert_eq!(written, 5);
+/// assert_eq!(output, b"[]\n[[1],2]\n[1,2]\n[3]\n3\n");
+///
+/// output.clear();
+/// ExternalSort::new().unique(true).reverse(true).sort(input.as_bytes(), &mut output).unwrap();
+/// assert_eq!(output, b"[3]\n[[1],2]\n[]\n");
+///
+/// let err = ExternalSort::new().sort("[1]\n[2,\n".as_bytes(), std::io::sink()).unwrap_err();
+/// assert_eq!(err.to_string(), "unbalanced bracket at line 2, column 1");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExternalSort {
+    unique: bool,
+    reverse: bool,
+    memory: usize,
+    temp_dir: Option<PathBuf>,
+}
+
+impl Default for ExternalSort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExternalSort {
+    /// A stable ascending sort keeping every packet, with a memory budget of 256 MiB
+    /// and temporary files in the system's temporary directory
+    pub fn new() -> Self {
+        ExternalSort {
+            unique: false,
+            reverse: false,
+            memory: 256 << 20,
+            temp_dir: None,
+        }
+    }
+
+    /// Whether to keep only the first of each run of equal packets
+    pub fn unique(mut self, unique: bool) -> Self {
+        self.unique = unique;
+        self
+    }
+
+    /// Whether to sort greatest first
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Roughly how many bytes of packets to hold in memory before spilling to disk.
+    /// At least one packet is always held.
+    pub fn memory(mut self, bytes: usize) -> Self {
+        self.memory = bytes;
+        self
+    }
+
+    /// The directory to spill sorted runs into
+    pub fn temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.temp_dir = Some(dir.into());
+        self
+    }
+
+    /// Sorts the packets read from `input` into `output`, skipping blank lines, and
+    /// returns how many packets were written
+    pub fn sort(&self, input: impl BufRead, output: impl Write) -> Result<usize, SortError> {
+        self.sort_as::<u8>(input, output)
+    }
+
+    /// Like [`sort`](Self::sort), but reading integers into `T` rather than `u8`
+    pub fn sort_as<T: PacketInt>(
+        &self,
+        mut input: impl BufRead,
+        mut output: impl Write,
+    ) -> Result<usize, SortError> {
+        let mut chunk = Vec::new();
+        let mut runs = Vec::new();
+        let (mut line, mut lines, mut bytes, mut held) = (Vec::new(), 0, 0, 0);
+
+        loop {
+            line.clear();
+            let read = input
+                .read_until(b'\n', &mut line)
+                .map_err(SortError::Read)?;
+            if read == 0 {
+                break;
+            }
+            let start = bytes;
+            (lines, bytes) = (lines + 1, bytes + read);
+
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let packet: Packet<T> =
+                Packet::from_bytes(&line).map_err(|e| e.shifted(lines - 1, start))?;
+            chunk.push(packet);
+
+            // Every byte of input makes at most one packet
+            held += read * size_of::<Packet<T>>();
+            if held >= self.memory {
+                runs.push(self.spill(self.sorted(&mut chunk))?);
+                held = 0;
+            }
+        }
+
+        if runs.is_empty() {
+            return self.write(self.sorted(&mut chunk), &mut output, SortError::Write);
+        }
+        if !chunk.is_empty() {
+            runs.push(self.spill(self.sorted(&mut chunk))?);
+        }
+
+        // Merge in passes, keeping runs in input order so the sort stays stable
+        while runs.len() > FAN_IN {
+            let mut remaining = runs.into_iter();
+            runs = Vec::new();
+
+            loop {
+                let batch: Vec<_> = remaining.by_ref().take(FAN_IN).collect();
+                if batch.is_empty() {
+                    break;
+                }
+                runs.push(self.spill(self.merge::<T>(batch)?)?);
+            }
+        }
+        self.write(self.merge::<T>(runs)?, &mut output, SortError::Write)
+    }
+
+    fn cmp<T: Ord>(&self, a: &Packet<T>, b: &Packet<T>) -> Ordering {
+        if self.reverse {
+            b.cmp(a)
+        } else {
+            a.cmp(b)
+        }
+    }
+
+    /// Sorts and empties a chunk of packets
+    fn sorted<T: PacketInt>(
+        &self,
+        chunk: &mut Vec<Packet<T>>,
+    ) -> impl Iterator<Item = io::Result<Packet<T>>> {
+        chunk.sort_by(|a, b| self.cmp(a, b));
+        std::mem::take(chunk).into_iter().map(Ok)
+    }
+
+    /// Writes sorted packets one per line, dropping repeats if `unique`. The packets
+    /// can only fail to be read from temporary files.
+    fn write<T: PacketInt>(
+        &self,
+        packets: impl Iterator<Item = io::Result<Packet<T>>>,
+        output: impl Write,
+        error: fn(io::Error) -> SortError,
+    ) -> Result<usize, SortError> {
+        let mut output = BufWriter::new(output);
+        let mut last: Option<Packet<T>> = None;
+        let mut written = 0;
+
+        for packet in packets {
+            let packet = packet.map_err(SortError::Temp)?;
+            if self.unique && last.as_ref() == Some(&packet) {
+                continue;
+            }
+
+            writeln!(output, "{packet}").map_err(error)?;
+            written += 1;
+            last = Some(packet);
+        }
+
+        output.flush().map_err(error)?;
+        Ok(written)
+    }
+
+    /// Writes sorted packets to a new temporary file, ready to be read back
+    fn spill<T: PacketInt>(
+        &self,
+        packets: impl Iterator<Item = io::Result<Packet<T>>>,
+    ) -> Result<Run, SortError> {
+        let file = match &self.temp_dir {
+            Some(dir) => tempfile::tempfile_in(dir),
+            None => tempfile::tempfile(),
+        };
+        let mut file = file.map_err(SortError::Temp)?;
+
+        self.write(packets, &mut file, SortError::Temp)?;
+        file.rewind().map_err(SortError::Temp)?;
+        Ok(Run {
+            reader: BufReader::new(file),
+            line: Vec::new(),
+        })
+    }
+
+    /// Merges sorted runs, taking equal packets from earlier runs first
+    fn merge<T: PacketInt>(&self, runs: Vec<Run>) -> Result<Merge<'_, T>, SortError> {
+        let mut merge = Merge {
+            sort: self,
+            runs,
+            heads: BinaryHeap::new(),
+        };
+
+        for run in 0..merge.runs.len() {
+            merge.advance(run).map_err(SortError::Temp)?;
+        }
+        Ok(merge)
+    }
+}
+
+/// A sorted run spilled to a temporary file, which is deleted once the run is dropped
+struct Run {
+    reader: BufReader<File>,
+    line: Vec<u8>,
+}
+
+impl Run {
+    fn next<T: PacketInt>(&mut self) -> io::Result<Option<Packet<T>>> {
+        self.line.clear();
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(None);
+        }
+
+        Packet::from_bytes(&self.line)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+struct Merge<'a, T> {
+    sort: &'a ExternalSort,
+    runs: Vec<Run>,
+    heads: BinaryHeap<Head<'a, T>>,
+}
+
+impl<T: PacketInt> Merge<'_, T> {
+    /// Reads the next packet of a run into the heap
+    fn advance(&mut self, run: usize) -> io::Result<()> {
+        if let Some(packet) = self.runs[run].next()? {
+            self.heads.push(Head {
+                sort: self.sort,
+                packet,
+                run,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T: PacketInt> Iterator for Merge<'_, T> {
+    type Item = io::Result<Packet<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Head { packet, run, .. } = self.heads.pop()?;
+        Some(self.advance(run).map(|()| packet))
+    }
+}
+
+/// The next packet of a run. Orders so that the packet to write next is the greatest,
+/// as [`BinaryHeap`] pops the greatest first.
+struct Head<'a, T> {
+    sort: &'a ExternalSort,
+    packet: Packet<T>,
+    run: usize,
+}
+
+impl<T: Ord> Ord for Head<'_, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort
+            .cmp(&other.packet, &self.packet)
+            .then(other.run.cmp(&self.run))
+    }
+}
+
+impl<T: Ord> PartialOrd for Head<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> PartialEq for Head<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl<T: Ord> Eq for Head<'_, T> {}
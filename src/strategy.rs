@@ -0,0 +1,68 @@
+use std::fmt::Debug;
+
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::collection::vec;
+use proptest::strategy::{BoxedStrategy, Strategy};
+
+use crate::{Packet, Pair};
+
+/// A proptest strategy for packets with integers drawn from `ints`, lists nested at
+/// most `depth` deep and lists of at most `items` items
+///
+/// Comparison bugs tend to hide in ties, so a small integer range finds more of them
+/// than the full range of `T`.
+/// # Examples
+/// ```
+/// use advent_of_code_2022_13::{packet_strategy, Packet};
+/// use proptest::prelude::*;
+///
+/// proptest!(|(packet in packet_strategy(0u8..4, 4, 4))| {
+///     prop_assert!(packet.to_string().parse::<Packet>().unwrap().identical(&packet));
+/// });
+/// ```
+pub fn packet_strategy<T: Debug + Clone + 'static>(
+    ints: impl Strategy<Value = T> + 'static,
+    depth: u32,
+    items: usize,
+) -> BoxedStrategy<Packet<T>> {
+    let size = depth * items as u32;
+
+    ints.prop_map(Packet::Int)
+        .prop_recursive(depth, size, items as u32, move |inner| {
+            vec(inner, 0..=items).prop_map(Packet::List)
+        })
+        .boxed()
+}
+
+/// A proptest strategy for pairs of packets from [`packet_strategy`]
+pub fn pair_strategy<T: Debug + Clone + 'static>(
+    ints: impl Strategy<Value = T> + 'static,
+    depth: u32,
+    items: usize,
+) -> BoxedStrategy<Pair<T>> {
+    let packets = packet_strategy(ints, depth, items);
+
+    (packets.clone(), packets)
+        .prop_map(|(left, right)| Pair { left, right })
+        .boxed()
+}
+
+/// Packets with any integers, nested at most 4 deep in lists of at most 6 items
+impl<T: Arbitrary + Clone + 'static> Arbitrary for Packet<T> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        packet_strategy(any::<T>(), 4, 6)
+    }
+}
+
+/// Pairs of packets as generated for [`Packet`]
+impl<T: Arbitrary + Clone + 'static> Arbitrary for Pair<T> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        pair_strategy(any::<T>(), 4, 6)
+    }
+}
@@ -0,0 +1,85 @@
+use std::cmp::Ordering;
+
+use advent_of_code_2022_13::{packet_strategy, pair_strategy, FlatPacket, Packet, Pair};
+use proptest::prelude::*;
+
+/// Small integers and short lists, so that ties and promoted integers come up often
+fn packets() -> BoxedStrategy<Packet> {
+    packet_strategy(0u8..4, 4, 4)
+}
+
+/// The comparison as the puzzle states it, written as plainly as possible
+fn reference(left: &Packet, right: &Packet) -> Ordering {
+    match (left, right) {
+        (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+        (Packet::Int(_), Packet::List(_)) => reference(&Packet::from(vec![left.clone()]), right),
+        (Packet::List(_), Packet::Int(_)) => reference(left, &Packet::from(vec![right.clone()])),
+        (Packet::List(a), Packet::List(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| reference(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+    }
+}
+
+proptest! {
+    #[test]
+    fn reflexive(a in packets()) {
+        prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+        prop_assert_eq!(&a, &a.clone());
+    }
+
+    #[test]
+    fn antisymmetric(a in packets(), b in packets()) {
+        prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+    }
+
+    #[test]
+    fn transitive(a in packets(), b in packets(), c in packets()) {
+        // Sorting makes a chain of orderings to check, which random triples rarely are
+        let mut packets = [a, b, c];
+        packets.sort();
+        let [a, b, c] = &packets;
+
+        prop_assert!(a <= b && b <= c && a <= c);
+        if a == b && b == c {
+            prop_assert_eq!(a, c);
+        }
+        if a < b || b < c {
+            prop_assert!(a < c);
+        }
+    }
+
+    #[test]
+    fn consistent_with_eq(a in packets(), b in packets()) {
+        prop_assert_eq!(a == b, a.cmp(&b).is_eq());
+        prop_assert_eq!(a.partial_cmp(&b), Some(a.cmp(&b)));
+    }
+
+    #[test]
+    fn matches_reference(a in packets(), b in packets()) {
+        prop_assert_eq!(a.cmp(&b), reference(&a, &b));
+    }
+
+    #[test]
+    fn other_orderings_agree(a in packets(), b in packets()) {
+        prop_assert_eq!(FlatPacket::from(&a).cmp(&FlatPacket::from(&b)), a.cmp(&b));
+        prop_assert_eq!(a.to_sort_key().cmp(&b.to_sort_key()), a.cmp(&b));
+        prop_assert_eq!(a.explain_cmp(&b).ordering, a.cmp(&b));
+    }
+
+    #[test]
+    fn packet_round_trips(a in any::<Packet<i64>>()) {
+        let parsed: Packet<i64> = a.to_string().parse().unwrap();
+        prop_assert!(parsed.identical(&a));
+    }
+
+    #[test]
+    fn pair_round_trips(pair in pair_strategy(any::<i32>(), 4, 6)) {
+        let parsed: Pair<i32> = format!("{}\n{}", pair.left(), pair.right()).parse().unwrap();
+        prop_assert!(parsed.left().identical(pair.left()));
+        prop_assert!(parsed.right().identical(pair.right()));
+        prop_assert_eq!(parsed.is_in_order(), pair.is_in_order());
+    }
+}